@@ -0,0 +1,3 @@
+-- Manual ordering of tasks within a lane
+ALTER TABLE tasks ADD COLUMN position INTEGER NOT NULL DEFAULT 0;
+UPDATE tasks SET position = id;
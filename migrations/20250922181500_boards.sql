@@ -0,0 +1,7 @@
+-- Multiple boards, each owning its own tasks
+CREATE TABLE IF NOT EXISTS boards(
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL
+);
+INSERT INTO boards (id, name) VALUES (1, 'Default');
+ALTER TABLE tasks ADD COLUMN board_id INTEGER NOT NULL DEFAULT 1;
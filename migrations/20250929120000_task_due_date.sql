@@ -0,0 +1,2 @@
+-- Optional due date stored as YYYY-MM-DD
+ALTER TABLE tasks ADD COLUMN due_date TEXT;
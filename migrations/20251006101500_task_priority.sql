@@ -0,0 +1,2 @@
+-- Task priority: 0 = low, 1 = normal, 2 = high, 3 = urgent
+ALTER TABLE tasks ADD COLUMN priority INTEGER NOT NULL DEFAULT 1;
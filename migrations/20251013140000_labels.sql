@@ -0,0 +1,12 @@
+-- Labels that can be attached to any number of tasks
+CREATE TABLE IF NOT EXISTS labels(
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE,
+    color TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS task_labels(
+    task_id INTEGER NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    label_id INTEGER NOT NULL REFERENCES labels(id) ON DELETE CASCADE,
+    PRIMARY KEY (task_id, label_id)
+);
@@ -0,0 +1,2 @@
+-- Archived tasks are hidden from the board instead of being deleted
+ALTER TABLE tasks ADD COLUMN archived_at TEXT;
@@ -0,0 +1,13 @@
+-- Track when a task entered its current lane so stale tasks can be archived
+ALTER TABLE tasks ADD COLUMN lane_entered_at TEXT;
+UPDATE tasks SET lane_entered_at = strftime('%Y-%m-%d %H:%M:%f', 'now');
+
+CREATE TRIGGER IF NOT EXISTS tasks_lane_entered_insert AFTER INSERT ON tasks
+WHEN new.lane_entered_at IS NULL BEGIN
+    UPDATE tasks SET lane_entered_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE id = new.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS tasks_lane_entered_update AFTER UPDATE OF lane ON tasks
+WHEN old.lane IS NOT new.lane BEGIN
+    UPDATE tasks SET lane_entered_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE id = new.id;
+END;
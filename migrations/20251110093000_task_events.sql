@@ -0,0 +1,60 @@
+-- Creation and last-change timestamps for every task
+ALTER TABLE tasks ADD COLUMN created_at TEXT;
+ALTER TABLE tasks ADD COLUMN updated_at TEXT;
+UPDATE tasks SET created_at = strftime('%Y-%m-%d %H:%M:%f', 'now'),
+    updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now');
+
+-- History of what happened to a task, shown as a timeline in the task dialog
+CREATE TABLE IF NOT EXISTS task_events(
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    task_id INTEGER NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL,
+    from_lane TEXT,
+    to_lane TEXT,
+    detail TEXT,
+    occurred_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS task_events_task ON task_events(task_id, occurred_at);
+
+INSERT INTO task_events (task_id, kind, to_lane, occurred_at)
+SELECT id, 'created', lane, created_at FROM tasks;
+
+CREATE TRIGGER IF NOT EXISTS tasks_created_insert AFTER INSERT ON tasks BEGIN
+    UPDATE tasks SET created_at = COALESCE(new.created_at, strftime('%Y-%m-%d %H:%M:%f', 'now')),
+        updated_at = COALESCE(new.updated_at, strftime('%Y-%m-%d %H:%M:%f', 'now'))
+    WHERE id = new.id;
+    INSERT INTO task_events (task_id, kind, to_lane, occurred_at)
+    VALUES (new.id, 'created', new.lane, strftime('%Y-%m-%d %H:%M:%f', 'now'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS tasks_updated AFTER UPDATE OF title, description, lane, due_date,
+    priority, archived_at ON tasks BEGIN
+    UPDATE tasks SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE id = new.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS tasks_moved_event AFTER UPDATE OF lane ON tasks
+WHEN old.lane IS NOT new.lane BEGIN
+    INSERT INTO task_events (task_id, kind, from_lane, to_lane, occurred_at)
+    VALUES (new.id, 'moved', old.lane, new.lane, strftime('%Y-%m-%d %H:%M:%f', 'now'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS tasks_edited_event AFTER UPDATE OF title, description, due_date,
+    priority ON tasks
+WHEN old.title IS NOT new.title OR old.description IS NOT new.description
+    OR old.due_date IS NOT new.due_date OR old.priority IS NOT new.priority BEGIN
+    INSERT INTO task_events (task_id, kind, detail, occurred_at)
+    VALUES (new.id, 'edited', rtrim(
+        CASE WHEN old.title IS NOT new.title THEN 'title, ' ELSE '' END ||
+        CASE WHEN old.description IS NOT new.description THEN 'description, ' ELSE '' END ||
+        CASE WHEN old.due_date IS NOT new.due_date THEN 'due date, ' ELSE '' END ||
+        CASE WHEN old.priority IS NOT new.priority THEN 'priority, ' ELSE '' END, ', '),
+        strftime('%Y-%m-%d %H:%M:%f', 'now'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS tasks_archived_event AFTER UPDATE OF archived_at ON tasks
+WHEN (old.archived_at IS NULL) IS NOT (new.archived_at IS NULL) BEGIN
+    INSERT INTO task_events (task_id, kind, occurred_at)
+    VALUES (new.id, CASE WHEN new.archived_at IS NULL THEN 'restored' ELSE 'archived' END,
+        strftime('%Y-%m-%d %H:%M:%f', 'now'));
+END;
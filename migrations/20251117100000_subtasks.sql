@@ -0,0 +1,10 @@
+-- Checklist items belonging to a task
+CREATE TABLE IF NOT EXISTS subtasks(
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    task_id INTEGER NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    title TEXT NOT NULL,
+    done BOOLEAN NOT NULL DEFAULT FALSE,
+    position INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE INDEX IF NOT EXISTS subtasks_task ON subtasks(task_id, position);
@@ -0,0 +1,7 @@
+-- Tasks that must be finished before another task can progress
+CREATE TABLE IF NOT EXISTS task_dependencies(
+    task_id INTEGER NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    blocked_by_id INTEGER NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    PRIMARY KEY (task_id, blocked_by_id),
+    CHECK (task_id != blocked_by_id)
+);
@@ -0,0 +1,27 @@
+-- Boards whose lanes are being renamed. Their tasks keep the same lane under a
+-- new name, so the lane triggers don't record a move or reset any timestamps.
+CREATE TABLE IF NOT EXISTS lane_renames(
+    board_id INTEGER PRIMARY KEY REFERENCES boards(id) ON DELETE CASCADE
+);
+
+DROP TRIGGER IF EXISTS tasks_updated;
+CREATE TRIGGER tasks_updated AFTER UPDATE OF title, description, lane, due_date,
+    priority, archived_at ON tasks
+WHEN NOT EXISTS (SELECT 1 FROM lane_renames WHERE board_id = new.board_id) BEGIN
+    UPDATE tasks SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE id = new.id;
+END;
+
+DROP TRIGGER IF EXISTS tasks_moved_event;
+CREATE TRIGGER tasks_moved_event AFTER UPDATE OF lane ON tasks
+WHEN old.lane IS NOT new.lane
+    AND NOT EXISTS (SELECT 1 FROM lane_renames WHERE board_id = new.board_id) BEGIN
+    INSERT INTO task_events (task_id, kind, from_lane, to_lane, occurred_at)
+    VALUES (new.id, 'moved', old.lane, new.lane, strftime('%Y-%m-%d %H:%M:%f', 'now'));
+END;
+
+DROP TRIGGER IF EXISTS tasks_lane_entered_update;
+CREATE TRIGGER tasks_lane_entered_update AFTER UPDATE OF lane ON tasks
+WHEN old.lane IS NOT new.lane
+    AND NOT EXISTS (SELECT 1 FROM lane_renames WHERE board_id = new.board_id) BEGIN
+    UPDATE tasks SET lane_entered_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE id = new.id;
+END;
@@ -1,12 +1,23 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::Local;
 use directories::BaseDirs;
+use iced::alignment::Vertical;
 use iced::futures::TryFutureExt;
-use iced::widget::{button, center, column, row, text};
-use iced::{Element, Length, Subscription, Task, window};
+use iced::widget::{button, center, checkbox, column, pick_list, row, text, text_input};
+use iced::{Element, Length, Subscription, Task, keyboard, mouse, window};
 use serde::{Deserialize, Serialize};
 use sqlx::migrate::MigrateDatabase;
 use sqlx::{Pool, Sqlite, SqlitePool};
 
-use crate::layout::modal;
+use crate::board::{self, Board};
+use crate::error::Error;
+use crate::lane::{self, Lane, LaneEditor};
+use crate::layout::{modal, notifications};
+use crate::repository::{SqliteTaskRepository, TaskRepository};
+use crate::stats::{self, Statistics};
 use crate::task;
 
 const TO_DO: &str = "To do";
@@ -16,71 +27,257 @@ const DONE: &str = "Done";
 const APP_DIR: &str = "todo_rs";
 const DB_NAME: &str = "tasks.db";
 const CONFIG_FILE: &str = "config.toml";
+const AUTO_ARCHIVE_INTERVAL: Duration = Duration::from_secs(60 * 60);
 
+#[allow(clippy::enum_variant_names)]
 #[derive(Debug, Clone)]
 pub enum Message {
+    SettingsLoaded(Result<(Config, PathBuf), Error>),
     Initialised(Pool<Sqlite>, Config),
+    StartupFailed(Error, Config, Option<PathBuf>),
+    RetryStartup,
+    DatabasePathUpdated(String),
+    BrowseDatabase,
+    DatabaseChosen(Option<PathBuf>),
+    OpenDatabase,
+    CreateDatabase,
+    BoardsLoaded(Result<Vec<Board>, Error>),
+    SwitchBoard(Board),
+    NewBoardNameUpdated(String),
+    CreateBoard,
+    BoardCreated(Result<i64, Error>),
+    OpenLaneEditor,
+    LaneMessage(lane::Message),
+    LanesMoved(Result<(), Error>),
+    SortByPriorityToggled(bool),
+    ShowScreen(Screen),
+    StatisticsLoaded(Result<Statistics, Error>),
+    AutoArchiveTick,
+    AutoArchived(Result<(), Error>),
     TaskMessage(task::Message),
     EventReceived(iced::Event),
+    ConfigSaved(Result<(), Error>),
+    ConfigStored(Result<(), Error>),
+    DismissNotification(usize),
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Config {
-    pub lanes: Vec<String>,
+    pub lanes: Vec<Lane>,
+    #[serde(default)]
+    pub current_board: Option<i64>,
+    #[serde(default)]
+    pub sort_by_priority: bool,
+    // Archive tasks that have sat in a board's final lane for this many days
+    #[serde(default)]
+    pub auto_archive_days: Option<u32>,
+    // Open this database file instead of the one in the data dir
+    #[serde(default)]
+    pub database: Option<PathBuf>,
+    #[serde(default)]
+    pub boards: BTreeMap<String, BoardConfig>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct BoardConfig {
+    pub lanes: Vec<Lane>,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
-            lanes: vec![TO_DO.into(), IN_PROGRESS.into(), DONE.into()],
+            lanes: vec![Lane::new(TO_DO), Lane::new(IN_PROGRESS), Lane::new(DONE)],
+            current_board: None,
+            sort_by_priority: false,
+            auto_archive_days: None,
+            database: None,
+            boards: BTreeMap::new(),
         }
     }
 }
 
+impl Config {
+    // Boards without their own lane definitions fall back to the global lanes
+    pub fn board_lanes(&self, board_id: i64) -> Vec<Lane> {
+        self.boards
+            .get(&board_id.to_string())
+            .map(|board| board.lanes.clone())
+            .unwrap_or_else(|| self.lanes.clone())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Screen {
+    #[default]
+    Board,
+    Archive,
+    Statistics,
+}
+
 pub struct Initialised {
     config: Config,
-    tasks_controller: task::ViewController,
+    repo: SqliteTaskRepository,
+    boards: Vec<Board>,
+    new_board_name: String,
+    lane_editor: Option<LaneEditor>,
+    screen: Screen,
+    statistics: Option<Statistics>,
+    notifications: Vec<Error>,
+    tasks_controller: task::ViewController<SqliteTaskRepository>,
+}
+
+impl Initialised {
+    fn switch_board(&mut self, board_id: i64) -> iced::Task<Message> {
+        self.config.current_board = Some(board_id);
+        self.screen = Screen::Board;
+        self.tasks_controller = task::ViewController::new(
+            self.repo.clone(),
+            board_id,
+            self.config.board_lanes(board_id),
+        );
+        self.tasks_controller
+            .set_sort_by_priority(self.config.sort_by_priority);
+        self.tasks_controller.load().map(Message::TaskMessage)
+    }
+
+    fn notify(&mut self, err: Error) {
+        self.notifications.push(err);
+    }
+}
+
+pub struct StartupFailure {
+    error: Error,
+    config: Config,
+    // The database that failed to open, if startup got that far
+    attempted: Option<PathBuf>,
+    database_path: String,
+}
+
+impl StartupFailure {
+    fn new(error: Error, config: Config, attempted: Option<PathBuf>) -> Self {
+        Self {
+            error,
+            config,
+            database_path: attempted
+                .as_ref()
+                .map(|path| path.display().to_string())
+                .unwrap_or_default(),
+            attempted,
+        }
+    }
+
+    fn view(&self) -> Element<'_, Message> {
+        let can_open = !self.database_path.trim().is_empty();
+        center(
+            column![
+                text("ToDo could not start").size(24),
+                text(self.error.to_string()).style(text::danger),
+                button("Retry").on_press(Message::RetryStartup),
+                text("Open a different database file"),
+                row![
+                    text_input("Database file", &self.database_path)
+                        .on_input(Message::DatabasePathUpdated)
+                        .on_submit_maybe(can_open.then_some(Message::OpenDatabase)),
+                    button("Browse...").on_press(Message::BrowseDatabase),
+                    button("Open").on_press_maybe(can_open.then_some(Message::OpenDatabase)),
+                ]
+                .spacing(8),
+                text("Or start again with an empty database. The current one is left as it is."),
+                button("Start with a new database").on_press(Message::CreateDatabase),
+            ]
+            .spacing(12)
+            .max_width(600),
+        )
+        .into()
+    }
 }
 
 pub enum App {
     Initiaising,
-    Initialised(Initialised),
+    Failed(Box<StartupFailure>),
+    Initialised(Box<Initialised>),
+}
+
+fn start() -> iced::Task<Message> {
+    iced::Task::perform(load_settings(), Message::SettingsLoaded)
+}
+
+// Only the default database and new ones are created, a chosen file must exist
+fn open(db_file: PathBuf, config: Config, create: bool) -> iced::Task<Message> {
+    iced::Task::perform(
+        open_database(db_file.clone(), create),
+        move |res| match res {
+            Ok(pool) => Message::Initialised(pool, config.clone()),
+            Err(err) => Message::StartupFailed(err, config.clone(), Some(db_file.clone())),
+        },
+    )
 }
 
 impl App {
     pub fn new() -> (Self, Task<Message>) {
-        (
-            Self::Initiaising,
-            iced::Task::perform(initialise_app(), |res| match res {
-                Ok((pool, config)) => Message::Initialised(pool, config),
-                Err(err) => panic!("failed to initialise app {err}"),
-            }),
-        )
+        (Self::Initiaising, start())
     }
 
     pub fn subscription(&self) -> Subscription<Message> {
-        iced::event::listen().map(|event| Message::EventReceived(event))
+        let events = iced::event::listen().map(Message::EventReceived);
+        match self {
+            App::Initialised(app) if app.config.auto_archive_days.is_some() => {
+                Subscription::batch([
+                    events,
+                    iced::time::every(AUTO_ARCHIVE_INTERVAL).map(|_| Message::AutoArchiveTick),
+                ])
+            }
+            _ => events,
+        }
     }
 
     pub fn update(&mut self, msg: Message) -> iced::Task<Message> {
         match self {
             App::Initiaising => self.update_initialising(msg),
+            App::Failed(_) => self.update_failed(msg),
             App::Initialised(app) => App::update_initialised(app, msg),
         }
     }
 
     fn update_initialising(&mut self, msg: Message) -> iced::Task<Message> {
         match msg {
+            Message::SettingsLoaded(Ok((config, db_file))) => {
+                let create = config.database.is_none();
+                open(db_file, config, create)
+            }
+            Message::SettingsLoaded(Err(err)) => {
+                *self = App::Failed(Box::new(StartupFailure::new(err, Config::default(), None)));
+                iced::Task::none()
+            }
+            Message::StartupFailed(err, config, attempted) => {
+                *self = App::Failed(Box::new(StartupFailure::new(err, config, attempted)));
+                iced::Task::none()
+            }
             Message::Initialised(pool, config) => {
-                let tasks_controller =
-                    task::ViewController::new(pool.clone(), config.lanes.clone());
-                *self = App::Initialised(Initialised {
+                let board_id = config.current_board.unwrap_or(board::DEFAULT_BOARD);
+                let repo = SqliteTaskRepository::new(pool);
+                let mut tasks_controller =
+                    task::ViewController::new(repo.clone(), board_id, config.board_lanes(board_id));
+                tasks_controller.set_sort_by_priority(config.sort_by_priority);
+                let load_tasks = tasks_controller.load().map(Message::TaskMessage);
+                let load_boards = iced::Task::perform(repo.get_boards(), Message::BoardsLoaded);
+                // Failures show up as a notification once the board is open
+                let archive = iced::Task::perform(
+                    auto_archive(repo.clone(), config.clone()),
+                    Message::AutoArchived,
+                );
+                *self = App::Initialised(Box::new(Initialised {
                     config,
+                    repo,
+                    boards: vec![],
+                    new_board_name: String::new(),
+                    lane_editor: None,
+                    screen: Screen::default(),
+                    statistics: None,
+                    notifications: vec![],
                     tasks_controller,
-                });
-                iced::Task::perform(task::get_tasks(pool), |res| {
-                    Message::TaskMessage(task::Message::TasksLoaded(res))
-                })
+                }));
+                iced::Task::batch([load_boards, load_tasks, archive])
             }
             Message::EventReceived(iced::Event::Window(iced::window::Event::CloseRequested)) => {
                 window::get_latest().and_then(window::close)
@@ -89,155 +286,510 @@ impl App {
         }
     }
 
+    fn update_failed(&mut self, msg: Message) -> iced::Task<Message> {
+        let App::Failed(failure) = self else {
+            return iced::Task::none();
+        };
+        // The chosen file is kept in the config so later launches use it too
+        let open_file = |db_file: PathBuf, failure: &StartupFailure, create: bool| {
+            let config = Config {
+                database: Some(db_file.clone()),
+                ..failure.config.clone()
+            };
+            open(db_file, config, create)
+        };
+        let task = match msg {
+            Message::RetryStartup => match &failure.attempted {
+                Some(db_file) => open(
+                    db_file.clone(),
+                    failure.config.clone(),
+                    failure.config.database.is_none(),
+                ),
+                None => start(),
+            },
+            Message::OpenDatabase => {
+                open_file(PathBuf::from(failure.database_path.trim()), failure, false)
+            }
+            Message::CreateDatabase => match fresh_database_file() {
+                Ok(db_file) => open_file(db_file, failure, true),
+                Err(err) => {
+                    failure.error = err;
+                    return iced::Task::none();
+                }
+            },
+            Message::DatabasePathUpdated(path) => {
+                failure.database_path = path;
+                return iced::Task::none();
+            }
+            Message::BrowseDatabase => {
+                return iced::Task::perform(pick_database(), Message::DatabaseChosen);
+            }
+            Message::DatabaseChosen(path) => {
+                if let Some(path) = path {
+                    failure.database_path = path.display().to_string();
+                }
+                return iced::Task::none();
+            }
+            Message::EventReceived(iced::Event::Window(iced::window::Event::CloseRequested)) => {
+                return window::get_latest().and_then(window::close);
+            }
+            _ => return iced::Task::none(),
+        };
+        *self = App::Initiaising;
+        task
+    }
+
     fn update_initialised(app: &mut Initialised, msg: Message) -> iced::Task<Message> {
         match msg {
-            Message::TaskMessage(task_msg) => app
-                .tasks_controller
-                .update(task_msg)
-                .map(Message::TaskMessage),
-            Message::EventReceived(event) => {
-                if let iced::Event::Window(iced::window::Event::CloseRequested) = event {
-                    iced::Task::future(save_config(app.config.clone()))
-                        .and_then(|_| window::get_latest())
-                        .and_then(window::close)
+            Message::BoardsLoaded(boards) => {
+                match boards {
+                    Ok(boards) => app.boards = boards,
+                    Err(err) => app.notify(err),
+                }
+                let board_id = app.tasks_controller.board_id();
+                match app.boards.first() {
+                    Some(first) if !app.boards.iter().any(|b| b.id == board_id) => {
+                        let first_id = first.id;
+                        app.switch_board(first_id)
+                    }
+                    _ => iced::Task::none(),
+                }
+            }
+            Message::SwitchBoard(board) => {
+                if board.id == app.tasks_controller.board_id() {
+                    iced::Task::none()
                 } else {
+                    app.switch_board(board.id)
+                }
+            }
+            Message::NewBoardNameUpdated(name) => {
+                app.new_board_name = name;
+                iced::Task::none()
+            }
+            Message::CreateBoard => {
+                let name = app.new_board_name.trim().to_string();
+                if name.is_empty() {
                     iced::Task::none()
+                } else {
+                    app.new_board_name.clear();
+                    iced::Task::perform(app.repo.insert_board(name), Message::BoardCreated)
                 }
             }
+            Message::BoardCreated(board_id) => match board_id {
+                Ok(board_id) => {
+                    // New boards start from the global lanes and keep them if those change
+                    let lanes = app.config.lanes.clone();
+                    app.config
+                        .boards
+                        .insert(board_id.to_string(), BoardConfig { lanes });
+                    let store_config =
+                        iced::Task::perform(save_config(app.config.clone()), Message::ConfigStored);
+                    app.switch_board(board_id)
+                        .chain(iced::Task::perform(
+                            app.repo.get_boards(),
+                            Message::BoardsLoaded,
+                        ))
+                        .chain(store_config)
+                }
+                Err(err) => {
+                    app.notify(err);
+                    iced::Task::none()
+                }
+            },
+            Message::OpenLaneEditor => {
+                app.lane_editor = Some(LaneEditor::new(app.tasks_controller.lanes()));
+                iced::Task::none()
+            }
+            Message::LaneMessage(lane::Message::Cancel) => {
+                app.lane_editor = None;
+                iced::Task::none()
+            }
+            Message::LaneMessage(lane::Message::Save) => {
+                let Some(changes) = app.lane_editor.as_mut().and_then(LaneEditor::changes) else {
+                    return iced::Task::none();
+                };
+                app.lane_editor = None;
+
+                let board_id = app.tasks_controller.board_id();
+                app.config.boards.insert(
+                    board_id.to_string(),
+                    BoardConfig {
+                        lanes: changes.lanes.clone(),
+                    },
+                );
+                app.tasks_controller.set_lanes(changes.lanes);
+                // The new lanes are only written to the config once the tasks have moved
+                let config = app.config.clone();
+                iced::Task::perform(
+                    app.repo
+                        .move_lanes(board_id, changes.renames, changes.moves)
+                        .and_then(|()| save_config(config)),
+                    Message::LanesMoved,
+                )
+            }
+            Message::LaneMessage(lane_msg) => {
+                if let Some(editor) = app.lane_editor.as_mut() {
+                    editor.update(lane_msg);
+                }
+                iced::Task::none()
+            }
+            Message::SortByPriorityToggled(sort_by_priority) => {
+                app.config.sort_by_priority = sort_by_priority;
+                app.tasks_controller.set_sort_by_priority(sort_by_priority);
+                iced::Task::none()
+            }
+            Message::ShowScreen(screen) => {
+                app.screen = screen;
+                match screen {
+                    Screen::Archive => app
+                        .tasks_controller
+                        .update(task::Message::LoadArchive)
+                        .map(Message::TaskMessage),
+                    Screen::Statistics => {
+                        app.statistics = None;
+                        iced::Task::perform(
+                            stats::load_statistics(
+                                app.repo.clone(),
+                                app.tasks_controller.board_id(),
+                                app.tasks_controller
+                                    .lanes()
+                                    .iter()
+                                    .map(|lane| lane.name.clone())
+                                    .collect(),
+                            ),
+                            Message::StatisticsLoaded,
+                        )
+                    }
+                    Screen::Board => iced::Task::none(),
+                }
+            }
+            Message::StatisticsLoaded(statistics) => {
+                match statistics {
+                    Ok(statistics) => app.statistics = Some(statistics),
+                    Err(err) => {
+                        app.notify(err);
+                        app.screen = Screen::Board;
+                    }
+                }
+                iced::Task::none()
+            }
+            Message::AutoArchiveTick => iced::Task::perform(
+                auto_archive(app.repo.clone(), app.config.clone()),
+                Message::AutoArchived,
+            ),
+            Message::AutoArchived(result) | Message::LanesMoved(result) => {
+                if let Err(err) = result {
+                    app.notify(err);
+                }
+                app.tasks_controller.load().map(Message::TaskMessage)
+            }
+            Message::TaskMessage(task_msg) => {
+                if let Some(err) = task_msg.error() {
+                    app.notify(err.clone());
+                }
+                app.tasks_controller
+                    .update(task_msg)
+                    .map(Message::TaskMessage)
+            }
+            Message::ConfigStored(result) => {
+                if let Err(err) = result {
+                    app.notify(err);
+                }
+                iced::Task::none()
+            }
+            Message::ConfigSaved(result) => match result {
+                Ok(()) => window::get_latest().and_then(window::close),
+                Err(err) => {
+                    app.notify(err);
+                    iced::Task::none()
+                }
+            },
+            Message::DismissNotification(idx) => {
+                if idx < app.notifications.len() {
+                    app.notifications.remove(idx);
+                }
+                iced::Task::none()
+            }
+            Message::EventReceived(event) => match event {
+                iced::Event::Window(iced::window::Event::CloseRequested) => {
+                    iced::Task::perform(save_config(app.config.clone()), Message::ConfigSaved)
+                }
+                iced::Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => app
+                    .tasks_controller
+                    .update(task::Message::DragEnded)
+                    .map(Message::TaskMessage),
+                iced::Event::Keyboard(keyboard::Event::KeyPressed {
+                    key: keyboard::Key::Character(c),
+                    modifiers,
+                    ..
+                }) if modifiers.command()
+                    && c.eq_ignore_ascii_case("z")
+                    && app.lane_editor.is_none()
+                    && !app.tasks_controller.is_modal_open() =>
+                {
+                    let msg = if modifiers.shift() {
+                        task::Message::Redo
+                    } else {
+                        task::Message::Undo
+                    };
+                    app.tasks_controller.update(msg).map(Message::TaskMessage)
+                }
+                _ => iced::Task::none(),
+            },
             _ => iced::Task::none(),
         }
     }
 
-    pub fn view(&self) -> Element<Message> {
+    pub fn view(&self) -> Element<'_, Message> {
         match self {
             App::Initiaising => center(text("Loading...")).into(),
+            App::Failed(failure) => failure.view(),
             App::Initialised(app) => {
+                let current_board = app
+                    .boards
+                    .iter()
+                    .find(|b| b.id == app.tasks_controller.board_id())
+                    .cloned();
+                let screen_content = || -> Element<'_, Message> {
+                    match app.screen {
+                        Screen::Board => column![
+                            app.tasks_controller.filter_view().map(Message::TaskMessage),
+                            app.tasks_controller.view().map(Message::TaskMessage),
+                        ]
+                        .spacing(4)
+                        .into(),
+                        Screen::Archive => column![
+                            row![
+                                button("Back to board")
+                                    .on_press(Message::ShowScreen(Screen::Board)),
+                                text("Archive").size(24),
+                            ]
+                            .spacing(8)
+                            .align_y(Vertical::Center),
+                            app.tasks_controller
+                                .archive_view()
+                                .map(Message::TaskMessage),
+                        ]
+                        .spacing(8)
+                        .into(),
+                        Screen::Statistics => column![
+                            row![
+                                button("Back to board")
+                                    .on_press(Message::ShowScreen(Screen::Board)),
+                                text("Statistics").size(24),
+                            ]
+                            .spacing(8)
+                            .align_y(Vertical::Center),
+                            match &app.statistics {
+                                Some(statistics) => statistics.view(),
+                                None => text("Loading statistics...").into(),
+                            },
+                        ]
+                        .spacing(8)
+                        .into(),
+                    }
+                };
                 let base_content = || {
                     column![
-                        row![button("Add Task").on_press(Message::TaskMessage(
-                            task::Message::OpenModal(task::Modal::NewTask)
-                        ))],
-                        app.tasks_controller.view().map(Message::TaskMessage),
+                        row![
+                            pick_list(
+                                app.boards.as_slice(),
+                                current_board.clone(),
+                                Message::SwitchBoard
+                            ),
+                            text_input("New board", &app.new_board_name)
+                                .on_input(Message::NewBoardNameUpdated)
+                                .on_submit(Message::CreateBoard)
+                                .width(Length::Fixed(200.0)),
+                            button("Add Board").on_press(Message::CreateBoard),
+                            button("Add Task").on_press(Message::TaskMessage(
+                                task::Message::OpenModal(task::Modal::NewTask)
+                            )),
+                            button("Edit Lanes").on_press(Message::OpenLaneEditor),
+                            button("Undo").on_press_maybe(
+                                app.tasks_controller
+                                    .can_undo()
+                                    .then_some(Message::TaskMessage(task::Message::Undo))
+                            ),
+                            button("Redo").on_press_maybe(
+                                app.tasks_controller
+                                    .can_redo()
+                                    .then_some(Message::TaskMessage(task::Message::Redo))
+                            ),
+                            checkbox("Sort by priority", app.config.sort_by_priority)
+                                .on_toggle(Message::SortByPriorityToggled),
+                            button("Archive").on_press(Message::ShowScreen(Screen::Archive)),
+                            button("Statistics").on_press(Message::ShowScreen(Screen::Statistics)),
+                        ]
+                        .spacing(8)
+                        .align_y(Vertical::Center),
+                        screen_content(),
                     ]
                     .width(Length::Fill)
                     .height(Length::Fill)
                     .spacing(4)
                 };
 
-                app.tasks_controller
-                    .modal_view()
-                    .map(|v| {
-                        modal(
-                            base_content(),
-                            v.map(Message::TaskMessage),
-                            Message::TaskMessage(task::Message::CloseModal),
-                        )
-                    })
-                    .unwrap_or(base_content().into())
+                let content = if let Some(editor) = &app.lane_editor {
+                    modal(
+                        base_content(),
+                        editor.view().map(Message::LaneMessage),
+                        Message::LaneMessage(lane::Message::Cancel),
+                    )
+                } else {
+                    app.tasks_controller
+                        .modal_view()
+                        .map(|v| {
+                            modal(
+                                base_content(),
+                                v.map(Message::TaskMessage),
+                                Message::TaskMessage(task::Message::CloseModal),
+                            )
+                        })
+                        .unwrap_or(base_content().into())
+                };
+                notifications(content, &app.notifications, Message::DismissNotification)
             }
         }
     }
 }
 
-async fn setup_app_dirs() -> Result<(), String> {
-    println!("Setting up app directories");
-    let dirs = BaseDirs::new().ok_or("Could not get directories")?;
+async fn setup_app_dirs() -> Result<(), Error> {
+    let dirs = BaseDirs::new().ok_or(Error::Directories)?;
     let data_dir = dirs.data_dir().join(APP_DIR);
     let conf_dir = dirs.config_dir().join(APP_DIR);
 
     let data_dir_exists = tokio::fs::try_exists(&data_dir)
-        .map_err(|_| format!("Could not check data dir existence"))
+        .map_err(Error::io("check the data dir exists"))
         .await?;
     let data = if data_dir_exists {
         Ok(())
     } else {
         tokio::fs::create_dir(&data_dir)
-            .map_err(|_| format!("Could not create data dir for app"))
+            .map_err(Error::io("create the data dir"))
             .await
     };
 
     let conf_dir_exists = tokio::fs::try_exists(&conf_dir)
-        .map_err(|_| format!("Could not check config dir existence"))
+        .map_err(Error::io("check the config dir exists"))
         .await?;
     let conf = if conf_dir_exists {
         Ok(())
     } else {
         tokio::fs::create_dir(&conf_dir)
-            .map_err(|_| format!("Could not create config dir for app"))
+            .map_err(Error::io("create the config dir"))
             .await
     };
 
-    data.and_then(|_| conf)
+    data.and(conf)
 }
 
-async fn setup_db_connection() -> Result<Pool<Sqlite>, String> {
-    println!("Setting up database connection");
-    let dirs = BaseDirs::new().ok_or("Could not get directories")?;
-    let data_dir = dirs.data_dir().join(APP_DIR);
-    let db_file = data_dir.join(DB_NAME);
+fn data_dir() -> Result<PathBuf, Error> {
+    BaseDirs::new()
+        .map(|dirs| dirs.data_dir().join(APP_DIR))
+        .ok_or(Error::Directories)
+}
+
+// New databases get a timestamped name so an existing file is never replaced
+fn fresh_database_file() -> Result<PathBuf, Error> {
+    let name = format!("tasks-{}.db", Local::now().format("%Y%m%d-%H%M%S"));
+    data_dir().map(|dir| dir.join(name))
+}
+
+async fn pick_database() -> Option<PathBuf> {
+    rfd::AsyncFileDialog::new()
+        .set_title("Open database")
+        .add_filter("SQLite database", &["db", "sqlite", "sqlite3"])
+        .pick_file()
+        .await
+        .map(|file| file.path().to_path_buf())
+}
+
+async fn setup_db_connection(db_file: &Path, create: bool) -> Result<Pool<Sqlite>, Error> {
     let db_url = db_file
         .to_str()
         .map(|s| format!("sqlite://{}", s))
-        .ok_or("Could not create valid db url")?;
+        .ok_or_else(|| Error::Invalid(format!("Invalid database path {}", db_file.display())))?;
 
     if !Sqlite::database_exists(&db_url).await.unwrap_or(false) {
-        match Sqlite::create_database(&db_url).await {
-            Ok(_) => {
-                SqlitePool::connect(&db_url)
-                    .map_err(|_| "Could not connect to db".into())
-                    .await
-            }
-            Err(_) => Err("error creating db".into()),
+        if !create {
+            return Err(Error::Invalid(format!(
+                "There is no database at {}",
+                db_file.display()
+            )));
         }
-    } else {
-        SqlitePool::connect(&db_url)
-            .map_err(|_| "Could not connect to db".into())
-            .await
+        Sqlite::create_database(&db_url)
+            .map_err(Error::db("creating database"))
+            .await?;
     }
+    SqlitePool::connect(&db_url)
+        .map_err(Error::db("connecting to database"))
+        .await
 }
 
-async fn migrate_db(pool: Pool<Sqlite>) -> Result<Pool<Sqlite>, String> {
-    println!("Migrating db");
+async fn migrate_db(pool: Pool<Sqlite>) -> Result<Pool<Sqlite>, Error> {
     sqlx::migrate!()
         .run(&pool)
-        .map_err(|_| String::from("failed to run migration"))
+        .map_err(|err| Error::Migration(err.to_string()))
         .await?;
     Ok(pool)
 }
 
-async fn load_config() -> Result<Config, String> {
-    println!("Loading config");
-    let dirs = BaseDirs::new().ok_or("Could not get directories")?;
+async fn load_config() -> Result<Config, Error> {
+    let dirs = BaseDirs::new().ok_or(Error::Directories)?;
     let conf_file = dirs
         .config_dir()
         .join(format!("{}/{}", APP_DIR, CONFIG_FILE));
     let contents = tokio::fs::read(conf_file)
-        .map_err(|err| format!("Unable to load config: {err}"))
+        .map_err(Error::io("load the config"))
         .await?;
-    toml::from_slice(&contents).map_err(|err| format!("Unable to parse config: {err}"))
+    toml::from_slice(&contents).map_err(|err| Error::Config(err.to_string()))
 }
 
-async fn save_config(config: Config) -> Result<(), String> {
-    println!("Saving config");
-    let dirs = BaseDirs::new().ok_or("Could not get directories")?;
+async fn save_config(config: Config) -> Result<(), Error> {
+    let dirs = BaseDirs::new().ok_or(Error::Directories)?;
     let conf_file = dirs
         .config_dir()
         .join(format!("{}/{}", APP_DIR, CONFIG_FILE));
-    let serialized = toml::to_string_pretty(&config)
-        .map_err(|err| format!("Config serialization error: {}", err))?;
+    let serialized =
+        toml::to_string_pretty(&config).map_err(|err| Error::Config(err.to_string()))?;
     tokio::fs::write(conf_file, serialized)
-        .map_err(|err| format!("Error saving Config: {}", err))
+        .map_err(Error::io("save the config"))
         .await
 }
 
-async fn initialise_app() -> Result<(Pool<Sqlite>, Config), String> {
-    let pool = setup_app_dirs()
-        .and_then(|_| setup_db_connection())
-        .and_then(|pool| migrate_db(pool))
-        .await?;
+pub async fn load_settings() -> Result<(Config, PathBuf), Error> {
+    setup_app_dirs().await?;
+    let config = load_config().await.unwrap_or_default();
+    let db_file = match &config.database {
+        Some(db_file) => db_file.clone(),
+        None => data_dir()?.join(DB_NAME),
+    };
+    Ok((config, db_file))
+}
 
-    match load_config().await {
-        Ok(config) => Ok((pool, config)),
-        Err(_) => Ok((pool, Default::default())),
+pub async fn open_database(db_file: PathBuf, create: bool) -> Result<Pool<Sqlite>, Error> {
+    setup_db_connection(&db_file, create)
+        .and_then(migrate_db)
+        .await
+}
+
+pub async fn initialise_app() -> Result<(Pool<Sqlite>, Config), Error> {
+    let (config, db_file) = load_settings().await?;
+    let pool = open_database(db_file, config.database.is_none()).await?;
+    auto_archive(SqliteTaskRepository::new(pool.clone()), config.clone()).await?;
+    Ok((pool, config))
+}
+
+async fn auto_archive(repo: impl TaskRepository, config: Config) -> Result<(), Error> {
+    let Some(days) = config.auto_archive_days else {
+        return Ok(());
+    };
+    for board in repo.get_boards().await? {
+        if let Some(final_lane) = config.board_lanes(board.id).last() {
+            repo.archive_stale_tasks(board.id, final_lane.name.clone(), days)
+                .await?;
+        }
     }
+    Ok(())
 }
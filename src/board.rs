@@ -0,0 +1,35 @@
+use std::fmt;
+
+use iced::futures::TryFutureExt;
+use sqlx::{Pool, Sqlite};
+
+use crate::error::Error;
+
+pub const DEFAULT_BOARD: i64 = 1;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Board {
+    pub id: i64,
+    pub name: String,
+}
+
+impl fmt::Display for Board {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+pub async fn get_boards(pool: Pool<Sqlite>) -> Result<Vec<Board>, Error> {
+    sqlx::query_as!(Board, "SELECT id, name FROM boards ORDER BY id")
+        .fetch_all(&pool)
+        .map_err(Error::db("loading boards"))
+        .await
+}
+
+pub async fn insert_board(pool: Pool<Sqlite>, name: String) -> Result<i64, Error> {
+    sqlx::query!("INSERT INTO boards (name) VALUES (?)", name)
+        .execute(&pool)
+        .map_err(Error::db("inserting board into db"))
+        .map_ok(|res| res.last_insert_rowid())
+        .await
+}
@@ -0,0 +1,234 @@
+use std::collections::HashMap;
+
+use chrono::{Days, Local, NaiveDate};
+use iced::alignment::Vertical;
+use iced::mouse;
+use iced::widget::canvas::{self, Frame, Geometry, Path, Stroke};
+use iced::widget::{Row, Space, column, container, row, text};
+use iced::{Color, Element, Length, Point, Rectangle, Renderer, Theme};
+
+use crate::event::{EventKind, TaskEvent};
+use crate::label::COLORS;
+
+const MAX_DAYS: u64 = 90;
+const AXIS_MARGIN: f32 = 40.0;
+
+// Number of tasks in each configured lane at the end of every day
+#[derive(Clone, Debug, Default)]
+pub struct CumulativeFlow {
+    lanes: Vec<String>,
+    days: Vec<NaiveDate>,
+    counts: Vec<Vec<usize>>,
+}
+
+impl CumulativeFlow {
+    pub fn from_events(events: &[TaskEvent], lanes: &[String], today: NaiveDate) -> Self {
+        let Some(first_day) = events
+            .first()
+            .map(|event| event.occurred_at.with_timezone(&Local).date_naive())
+        else {
+            return Self::default();
+        };
+        let first_day = first_day.max(today - Days::new(MAX_DAYS));
+
+        // Archived tasks only keep counting once they're done, but restoring
+        // puts them back in the lane they were archived from
+        let final_lane = lanes.last().map(String::as_str);
+        let mut last_lanes: HashMap<i64, &str> = HashMap::new();
+        let mut task_lanes: HashMap<i64, &str> = HashMap::new();
+        let mut pending = events.iter().peekable();
+        let mut days = vec![];
+        let mut counts = vec![];
+        let mut day = first_day;
+        while day <= today {
+            while let Some(event) =
+                pending.next_if(|e| e.occurred_at.with_timezone(&Local).date_naive() <= day)
+            {
+                match (event.kind, &event.to_lane) {
+                    (EventKind::Created | EventKind::Moved, Some(lane)) => {
+                        last_lanes.insert(event.task_id, lane);
+                        task_lanes.insert(event.task_id, lane);
+                    }
+                    (EventKind::Archived, _)
+                        if task_lanes.get(&event.task_id).copied() != final_lane =>
+                    {
+                        task_lanes.remove(&event.task_id);
+                    }
+                    (EventKind::Restored, _) => {
+                        if let Some(lane) = last_lanes.get(&event.task_id) {
+                            task_lanes.insert(event.task_id, lane);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            counts.push(
+                lanes
+                    .iter()
+                    .map(|lane| task_lanes.values().filter(|l| **l == lane).count())
+                    .collect(),
+            );
+            days.push(day);
+            day = day + Days::new(1);
+        }
+
+        Self {
+            lanes: lanes.to_vec(),
+            days,
+            counts,
+        }
+    }
+
+    pub fn view<'a, Message: 'a>(&'a self) -> Element<'a, Message> {
+        if self.days.is_empty() {
+            return text("No lane history yet").into();
+        }
+        let legend = self.lanes.iter().enumerate().map(|(idx, lane)| {
+            let color = lane_color(idx);
+            row![
+                container(Space::new(Length::Fixed(12.0), Length::Fixed(12.0)))
+                    .style(move |_theme: &Theme| container::Style::default().background(color)),
+                text(lane).size(12),
+            ]
+            .spacing(4)
+            .align_y(Vertical::Center)
+            .into()
+        });
+        column![
+            canvas::Canvas::new(self)
+                .width(Length::Fill)
+                .height(Length::Fixed(240.0)),
+            Row::with_children(legend).spacing(12),
+        ]
+        .spacing(4)
+        .into()
+    }
+}
+
+fn lane_color(idx: usize) -> Color {
+    Color::parse(COLORS[idx % COLORS.len()]).unwrap_or(Color::WHITE)
+}
+
+impl<Message> canvas::Program<Message> for CumulativeFlow {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &Renderer,
+        theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let text_color = theme.palette().text;
+        let max_total = self
+            .counts
+            .iter()
+            .map(|day| day.iter().sum::<usize>())
+            .max()
+            .unwrap_or_default()
+            .max(1);
+
+        let width = bounds.width - AXIS_MARGIN;
+        let height = bounds.height - AXIS_MARGIN / 2.0;
+        let step = width / (self.days.len().max(2) - 1) as f32;
+        let x = |day: usize| AXIS_MARGIN + day as f32 * step;
+        let y = |total: usize| height - total as f32 / max_total as f32 * height;
+
+        // Bands are stacked with the final lane at the bottom, as is usual for CFDs
+        let mut below = vec![0; self.days.len()];
+        for idx in (0..self.lanes.len()).rev() {
+            let above: Vec<usize> = below
+                .iter()
+                .zip(&self.counts)
+                .map(|(base, day)| base + day[idx])
+                .collect();
+            let band = Path::new(|b| {
+                b.move_to(Point::new(x(0), y(above[0])));
+                for (day, total) in above.iter().enumerate() {
+                    b.line_to(Point::new(x(day), y(*total)));
+                }
+                if self.days.len() == 1 {
+                    b.line_to(Point::new(x(1), y(above[0])));
+                    b.line_to(Point::new(x(1), y(below[0])));
+                }
+                for (day, total) in below.iter().enumerate().rev() {
+                    b.line_to(Point::new(x(day), y(*total)));
+                }
+                b.close();
+            });
+            frame.fill(&band, lane_color(idx));
+            below = above;
+        }
+
+        let axes = Path::new(|b| {
+            b.move_to(Point::new(AXIS_MARGIN, 0.0));
+            b.line_to(Point::new(AXIS_MARGIN, height));
+            b.line_to(Point::new(bounds.width, height));
+        });
+        frame.stroke(&axes, Stroke::default().with_color(text_color));
+
+        let label = |content: String, position: Point| canvas::Text {
+            content,
+            position,
+            color: text_color,
+            size: 12.0.into(),
+            ..canvas::Text::default()
+        };
+        frame.fill_text(label(max_total.to_string(), Point::new(4.0, 0.0)));
+        frame.fill_text(label("0".into(), Point::new(4.0, height - 12.0)));
+        frame.fill_text(label(
+            self.days[0].format("%b %d").to_string(),
+            Point::new(AXIS_MARGIN, height + 4.0),
+        ));
+        if let Some(last) = self.days.last() {
+            frame.fill_text(label(
+                last.format("%b %d").to_string(),
+                Point::new(bounds.width - AXIS_MARGIN, height + 4.0),
+            ));
+        }
+
+        vec![frame.into_geometry()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+
+    fn event(task_id: i64, kind: EventKind, to_lane: Option<&str>, day: u32) -> TaskEvent {
+        TaskEvent {
+            task_id,
+            kind,
+            from_lane: None,
+            to_lane: to_lane.map(String::from),
+            detail: None,
+            occurred_at: Utc.with_ymd_and_hms(2025, 11, day, 12, 0, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn archived_tasks_leave_all_but_the_final_lane() {
+        let lanes = vec!["Todo".to_string(), "Doing".to_string(), "Done".to_string()];
+        let events = [
+            event(1, EventKind::Created, Some("Todo"), 1),
+            event(2, EventKind::Created, Some("Todo"), 1),
+            event(2, EventKind::Moved, Some("Done"), 2),
+            event(1, EventKind::Archived, None, 3),
+            event(2, EventKind::Archived, None, 3),
+            event(1, EventKind::Restored, None, 4),
+        ];
+        let today = Local
+            .from_utc_datetime(&events[5].occurred_at.naive_utc())
+            .date_naive();
+        let flow = CumulativeFlow::from_events(&events, &lanes, today);
+
+        assert_eq!(flow.counts.len(), 4);
+        assert_eq!(flow.counts[1], vec![1, 0, 1]);
+        assert_eq!(flow.counts[2], vec![0, 0, 1]);
+        assert_eq!(flow.counts[3], vec![1, 0, 1]);
+    }
+}
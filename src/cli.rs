@@ -0,0 +1,380 @@
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use sqlx::{Pool, Sqlite};
+
+use crate::app::{Config, initialise_app};
+use crate::board;
+use crate::error::Error;
+use crate::lane::Lane;
+use crate::model::{NewTask, Priority, Task, next_position};
+use crate::repository::{SqliteTaskRepository, TaskRepository};
+use crate::tui;
+
+const USAGE: &str = "Usage: todo-rs [COMMAND]
+
+Without a command the board window is opened.
+
+Commands:
+  add <title> [--lane <lane>] [--description <text>] [--priority <priority>] [--due <YYYY-MM-DD>]
+  list [--lane <lane>] [--archived]
+  move <id> <lane>
+  done <id>              Move a task to the board's final lane
+  rm <id> [--purge]      Archive a task, or delete it permanently with --purge
+  show <id>
+  tui                    Open the board in the terminal
+  help
+
+Every command accepts --board <name> to work on a board other than the current one.";
+
+enum Command {
+    Add,
+    List,
+    Move,
+    Done,
+    Remove,
+    Show,
+    Tui,
+}
+
+impl Command {
+    fn parse(command: &str) -> Option<Self> {
+        match command {
+            "add" => Some(Command::Add),
+            "list" | "ls" => Some(Command::List),
+            "move" | "mv" => Some(Command::Move),
+            "done" => Some(Command::Done),
+            "rm" => Some(Command::Remove),
+            "show" => Some(Command::Show),
+            "tui" => Some(Command::Tui),
+            _ => None,
+        }
+    }
+}
+
+// Arguments split into positionals and `--flag value` options. Flags listed in
+// `switches` take no value.
+struct Args {
+    positional: Vec<String>,
+    options: Vec<(String, Option<String>)>,
+}
+
+impl Args {
+    fn parse(args: Vec<String>, switches: &[&str]) -> Result<Self, Error> {
+        let mut positional = vec![];
+        let mut options = vec![];
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.strip_prefix("--") {
+                Some(flag) if switches.contains(&flag) => options.push((flag.to_string(), None)),
+                Some(flag) => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| Error::Invalid(format!("Missing value for --{flag}")))?;
+                    options.push((flag.to_string(), Some(value)));
+                }
+                None => positional.push(arg),
+            }
+        }
+        Ok(Self {
+            positional,
+            options,
+        })
+    }
+
+    fn option(&self, flag: &str) -> Option<&str> {
+        self.options
+            .iter()
+            .find(|(name, _)| name == flag)
+            .and_then(|(_, value)| value.as_deref())
+    }
+
+    fn switch(&self, flag: &str) -> bool {
+        self.options.iter().any(|(name, _)| name == flag)
+    }
+
+    fn only_options(&self, allowed: &[&str]) -> Result<(), Error> {
+        match self
+            .options
+            .iter()
+            .find(|(name, _)| !allowed.contains(&name.as_str()))
+        {
+            Some((name, _)) => Err(Error::Invalid(format!("Unknown option --{name}"))),
+            None => Ok(()),
+        }
+    }
+
+    fn task_id(&self) -> Result<i64, Error> {
+        let id = self
+            .positional
+            .first()
+            .ok_or_else(|| Error::Invalid("Missing task id".into()))?;
+        id.trim_start_matches('#')
+            .parse()
+            .map_err(|_| Error::Invalid(format!("Invalid task id: {id}")))
+    }
+}
+
+struct Context {
+    repo: SqliteTaskRepository,
+    board_id: i64,
+    lanes: Vec<Lane>,
+}
+
+impl Context {
+    async fn new(config: Config, pool: Pool<Sqlite>, board: Option<&str>) -> Result<Self, Error> {
+        let repo = SqliteTaskRepository::new(pool);
+        let board_id = match board {
+            Some(name) => repo
+                .get_boards()
+                .await?
+                .into_iter()
+                .find(|b| b.name.eq_ignore_ascii_case(name) || b.id.to_string() == name)
+                .map(|b| b.id)
+                .ok_or_else(|| Error::Invalid(format!("No board named \"{name}\"")))?,
+            None => config.current_board.unwrap_or(board::DEFAULT_BOARD),
+        };
+        Ok(Self {
+            lanes: config.board_lanes(board_id),
+            repo,
+            board_id,
+        })
+    }
+
+    // Lane names are matched case-insensitively so they're easy to type
+    fn lane(&self, name: &str) -> Result<&Lane, Error> {
+        self.lanes
+            .iter()
+            .find(|lane| lane.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| {
+                let names: Vec<&str> = self.lanes.iter().map(|l| l.name.as_str()).collect();
+                Error::Invalid(format!(
+                    "Unknown lane \"{name}\", expected one of: {}",
+                    names.join(", ")
+                ))
+            })
+    }
+
+    fn check_wip_limit(&self, lane: &Lane, tasks: &[Task]) -> Result<(), Error> {
+        let count = tasks.iter().filter(|t| t.lane == lane.name).count();
+        match lane.wip_warning(count) {
+            Some(warning) => Err(Error::Invalid(warning)),
+            None => Ok(()),
+        }
+    }
+
+    async fn find_task(&self, task_id: i64) -> Result<Task, Error> {
+        self.repo
+            .get_tasks(self.board_id)
+            .await?
+            .into_iter()
+            .chain(self.repo.get_archived_tasks(self.board_id).await?)
+            .find(|task| task.id == task_id)
+            .ok_or_else(|| Error::Invalid(format!("No task #{task_id} on this board")))
+    }
+}
+
+fn no_lanes() -> Error {
+    Error::Invalid("The board has no lanes".into())
+}
+
+pub fn run(args: Vec<String>) -> Result<(), Error> {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(Error::io("start the runtime"))?
+        .block_on(run_command(args))
+}
+
+async fn run_command(mut args: Vec<String>) -> Result<(), Error> {
+    let command = args.remove(0);
+    if matches!(command.as_str(), "help" | "--help" | "-h") {
+        println!("{USAGE}");
+        return Ok(());
+    }
+    let command = Command::parse(&command)
+        .ok_or_else(|| Error::Invalid(format!("Unknown command \"{command}\"\n\n{USAGE}")))?;
+    let args = Args::parse(args, &["archived", "purge"])?;
+    let (pool, config) = initialise_app().await?;
+    let ctx = Context::new(config, pool, args.option("board")).await?;
+
+    match command {
+        Command::Add => add(&ctx, &args).await,
+        Command::List => list(&ctx, &args).await,
+        Command::Move => move_task(&ctx, &args).await,
+        Command::Done => done(&ctx, &args).await,
+        Command::Remove => rm(&ctx, &args).await,
+        Command::Show => show(&ctx, &args).await,
+        Command::Tui => {
+            args.only_options(&["board"])?;
+            tui::run(ctx.repo, ctx.board_id, ctx.lanes).await
+        }
+    }
+}
+
+async fn add(ctx: &Context, args: &Args) -> Result<(), Error> {
+    args.only_options(&["board", "lane", "description", "priority", "due"])?;
+    let title = args.positional.join(" ");
+    if title.trim().is_empty() {
+        return Err(Error::Invalid("Missing task title".into()));
+    }
+    let lane = match args.option("lane") {
+        Some(name) => ctx.lane(name)?,
+        None => ctx.lanes.first().ok_or_else(no_lanes)?,
+    };
+    let priority = match args.option("priority") {
+        Some(name) => Priority::ALL
+            .into_iter()
+            .find(|p| p.to_string().eq_ignore_ascii_case(name))
+            .ok_or_else(|| Error::Invalid(format!("Unknown priority \"{name}\"")))?,
+        None => Priority::default(),
+    };
+    let due_date = args
+        .option("due")
+        .map(|date| {
+            NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| {
+                Error::Invalid(format!("Invalid due date \"{date}\", expected YYYY-MM-DD"))
+            })
+        })
+        .transpose()?;
+    ctx.check_wip_limit(lane, &ctx.repo.get_tasks(ctx.board_id).await?)?;
+
+    let task = NewTask::new(
+        ctx.board_id,
+        title,
+        args.option("description").map(String::from),
+        lane.name.clone(),
+        due_date,
+        priority,
+        vec![],
+    );
+    let task = ctx.repo.insert_task(task).await?;
+    println!("Added #{} to {}", task.id, task.lane);
+    Ok(())
+}
+
+async fn list(ctx: &Context, args: &Args) -> Result<(), Error> {
+    args.only_options(&["board", "lane", "archived"])?;
+    let lane_filter = args.option("lane").map(|name| ctx.lane(name)).transpose()?;
+    if args.switch("archived") {
+        for task in ctx.repo.get_archived_tasks(ctx.board_id).await? {
+            if lane_filter.is_none_or(|lane| lane.name == task.lane) {
+                println!("{}", task_line(&task));
+            }
+        }
+        return Ok(());
+    }
+
+    let tasks = ctx.repo.get_tasks(ctx.board_id).await?;
+    for lane in &ctx.lanes {
+        if lane_filter.is_some_and(|filter| filter.name != lane.name) {
+            continue;
+        }
+        let lane_tasks: Vec<&Task> = tasks.iter().filter(|t| t.lane == lane.name).collect();
+        match lane.wip_limit {
+            Some(limit) => println!("{} ({}/{})", lane.name, lane_tasks.len(), limit),
+            None => println!("{} ({})", lane.name, lane_tasks.len()),
+        }
+        for task in lane_tasks {
+            println!("  {}", task_line(task));
+        }
+    }
+    Ok(())
+}
+
+fn task_line(task: &Task) -> String {
+    let mut line = format!("#{:<4} [{}] {}", task.id, task.priority, task.title);
+    if let Some(due_date) = task.due_date {
+        line.push_str(&format!("  due {}", due_date.format("%Y-%m-%d")));
+    }
+    if !task.labels.is_empty() {
+        let labels: Vec<&str> = task.labels.iter().map(|l| l.name.as_str()).collect();
+        line.push_str(&format!("  ({})", labels.join(", ")));
+    }
+    line
+}
+
+async fn move_to(ctx: &Context, task_id: i64, lane: &Lane) -> Result<(), Error> {
+    let tasks = ctx.repo.get_tasks(ctx.board_id).await?;
+    let mut task = tasks
+        .iter()
+        .find(|task| task.id == task_id)
+        .cloned()
+        .ok_or_else(|| Error::Invalid(format!("No task #{task_id} on the board")))?;
+    if task.lane == lane.name {
+        println!("#{} is already in {}", task.id, task.lane);
+        return Ok(());
+    }
+    ctx.check_wip_limit(lane, &tasks)?;
+    if let Some(warning) = task.blocked_warning(&lane.name, &tasks, &ctx.lanes) {
+        eprintln!("Warning: {warning}");
+    }
+
+    task.position = next_position(&tasks, &lane.name);
+    task.lane = lane.name.clone();
+    ctx.repo.edit_task(task).await?;
+    println!("Moved #{task_id} to {}", lane.name);
+    Ok(())
+}
+
+async fn move_task(ctx: &Context, args: &Args) -> Result<(), Error> {
+    args.only_options(&["board"])?;
+    let task_id = args.task_id()?;
+    let lane = args.positional[1..].join(" ");
+    if lane.is_empty() {
+        return Err(Error::Invalid("Missing lane to move the task to".into()));
+    }
+    move_to(ctx, task_id, ctx.lane(&lane)?).await
+}
+
+async fn done(ctx: &Context, args: &Args) -> Result<(), Error> {
+    args.only_options(&["board"])?;
+    let final_lane = ctx.lanes.last().ok_or_else(no_lanes)?;
+    move_to(ctx, args.task_id()?, final_lane).await
+}
+
+async fn rm(ctx: &Context, args: &Args) -> Result<(), Error> {
+    args.only_options(&["board", "purge"])?;
+    let task = ctx.find_task(args.task_id()?).await?;
+    if args.switch("purge") {
+        ctx.repo.remove_task(task.id).await?;
+        println!("Deleted #{}", task.id);
+    } else {
+        ctx.repo.archive_task(task.id, Some(Utc::now())).await?;
+        println!("Archived #{}", task.id);
+    }
+    Ok(())
+}
+
+async fn show(ctx: &Context, args: &Args) -> Result<(), Error> {
+    args.only_options(&["board"])?;
+    let task = ctx.find_task(args.task_id()?).await?;
+    println!("#{} {}", task.id, task.title);
+    println!("Lane:     {}", task.lane);
+    println!("Priority: {}", task.priority);
+    if let Some(due_date) = task.due_date {
+        println!("Due:      {}", due_date.format("%Y-%m-%d"));
+    }
+    if !task.labels.is_empty() {
+        let labels: Vec<&str> = task.labels.iter().map(|l| l.name.as_str()).collect();
+        println!("Labels:   {}", labels.join(", "));
+    }
+    let timestamp = |at: DateTime<Utc>| at.with_timezone(&Local).format("%Y-%m-%d %H:%M");
+    println!("Created:  {}", timestamp(task.created_at));
+    println!("Updated:  {}", timestamp(task.updated_at));
+    if let Some(archived_at) = task.archived_at {
+        println!("Archived: {}", timestamp(archived_at));
+    }
+    if let Some(desc) = task.description.as_deref().filter(|d| !d.trim().is_empty()) {
+        println!("\n{}", desc.trim_end());
+    }
+    if !task.subtasks.is_empty() {
+        println!();
+        for subtask in &task.subtasks {
+            println!(
+                "[{}] {}",
+                if subtask.done { "x" } else { " " },
+                subtask.title
+            );
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use iced::futures::TryFutureExt;
+use sqlx::{Pool, Sqlite};
+
+use crate::error::Error;
+
+pub async fn set_task_dependencies(
+    pool: Pool<Sqlite>,
+    task_id: i64,
+    blocked_by: Vec<i64>,
+) -> Result<(), Error> {
+    let mut tx = pool
+        .begin()
+        .map_err(Error::db("starting transaction"))
+        .await?;
+    sqlx::query!("DELETE FROM task_dependencies WHERE task_id = ?", task_id)
+        .execute(&mut *tx)
+        .map_err(Error::db("clearing task dependencies"))
+        .await?;
+    for blocked_by_id in blocked_by {
+        sqlx::query!(
+            "INSERT INTO task_dependencies (task_id, blocked_by_id) VALUES (?, ?)",
+            task_id,
+            blocked_by_id
+        )
+        .execute(&mut *tx)
+        .map_err(Error::db("adding task dependency"))
+        .await?;
+    }
+    tx.commit()
+        .map_err(Error::db("committing task dependencies"))
+        .await
+}
@@ -0,0 +1,49 @@
+use std::fmt;
+
+// Sources are kept as messages so errors can be cloned into iced messages
+#[derive(Clone, Debug)]
+pub enum Error {
+    Directories,
+    Io {
+        action: &'static str,
+        message: String,
+    },
+    Config(String),
+    Database {
+        action: &'static str,
+        message: String,
+    },
+    Migration(String),
+    Invalid(String),
+}
+
+impl Error {
+    pub fn db(action: &'static str) -> impl FnOnce(sqlx::Error) -> Self {
+        move |err| Error::Database {
+            action,
+            message: err.to_string(),
+        }
+    }
+
+    pub fn io(action: &'static str) -> impl FnOnce(std::io::Error) -> Self {
+        move |err| Error::Io {
+            action,
+            message: err.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Directories => write!(f, "Could not find the data and config directories"),
+            Error::Io { action, message } => write!(f, "Could not {action}: {message}"),
+            Error::Config(message) => write!(f, "Config error: {message}"),
+            Error::Database { action, message } => write!(f, "Error {action}: {message}"),
+            Error::Migration(message) => write!(f, "Failed to migrate the database: {message}"),
+            Error::Invalid(message) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
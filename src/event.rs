@@ -0,0 +1,79 @@
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use iced::futures::TryFutureExt;
+use sqlx::{Pool, Sqlite};
+
+use crate::error::Error;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "TEXT", rename_all = "lowercase")]
+pub enum EventKind {
+    Created,
+    Moved,
+    Edited,
+    Archived,
+    Restored,
+}
+
+// Events are written by triggers on the tasks table, see the task_events migration
+#[derive(Clone, Debug)]
+pub struct TaskEvent {
+    pub task_id: i64,
+    pub kind: EventKind,
+    pub from_lane: Option<String>,
+    pub to_lane: Option<String>,
+    pub detail: Option<String>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+impl fmt::Display for TaskEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let lane = |lane: &Option<String>| lane.clone().unwrap_or_default();
+        match self.kind {
+            EventKind::Created => write!(f, "Created in {}", lane(&self.to_lane)),
+            EventKind::Moved => write!(
+                f,
+                "Moved from {} to {}",
+                lane(&self.from_lane),
+                lane(&self.to_lane)
+            ),
+            EventKind::Edited => match &self.detail {
+                Some(detail) if !detail.is_empty() => write!(f, "Edited {detail}"),
+                _ => write!(f, "Edited"),
+            },
+            EventKind::Archived => write!(f, "Archived"),
+            EventKind::Restored => write!(f, "Restored"),
+        }
+    }
+}
+
+pub async fn get_task_events(pool: Pool<Sqlite>, task_id: i64) -> Result<Vec<TaskEvent>, Error> {
+    sqlx::query_as!(
+        TaskEvent,
+        r#"SELECT task_id, kind as "kind: EventKind", from_lane, to_lane, detail,
+            occurred_at as "occurred_at: DateTime<Utc>"
+        FROM task_events WHERE task_id = ? ORDER BY occurred_at, id"#,
+        task_id
+    )
+    .fetch_all(&pool)
+    .map_err(Error::db("loading task history"))
+    .await
+}
+
+// Includes archived tasks so finished work still counts towards statistics
+pub async fn get_board_events(pool: Pool<Sqlite>, board_id: i64) -> Result<Vec<TaskEvent>, Error> {
+    sqlx::query_as!(
+        TaskEvent,
+        r#"SELECT task_events.task_id, kind as "kind: EventKind", from_lane, to_lane, detail,
+            occurred_at as "occurred_at: DateTime<Utc>"
+        FROM task_events
+        JOIN tasks ON tasks.id = task_events.task_id
+        WHERE tasks.board_id = ?
+        ORDER BY occurred_at, task_events.id"#,
+        board_id
+    )
+    .fetch_all(&pool)
+    .map_err(Error::db("loading board history"))
+    .await
+}
@@ -0,0 +1,91 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use chrono::{Days, Local};
+
+use crate::model::{Priority, Task};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DueFilter {
+    Overdue,
+    DueToday,
+    DueThisWeek,
+    NoDueDate,
+}
+
+impl DueFilter {
+    pub const ALL: [DueFilter; 4] = [
+        DueFilter::Overdue,
+        DueFilter::DueToday,
+        DueFilter::DueThisWeek,
+        DueFilter::NoDueDate,
+    ];
+}
+
+impl fmt::Display for DueFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DueFilter::Overdue => "Overdue",
+            DueFilter::DueToday => "Due today",
+            DueFilter::DueThisWeek => "Due within a week",
+            DueFilter::NoDueDate => "No due date",
+        };
+        write!(f, "{name}")
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Filter {
+    pub text: String,
+    pub label: Option<i64>,
+    pub priority: Option<Priority>,
+    pub due: Option<DueFilter>,
+    // Ids matched by the full-text index for `text`, once the search has run
+    pub matching_ids: Option<HashSet<i64>>,
+}
+
+impl Filter {
+    pub fn is_active(&self) -> bool {
+        !self.text.trim().is_empty()
+            || self.label.is_some()
+            || self.priority.is_some()
+            || self.due.is_some()
+    }
+
+    pub fn matches(&self, task: &Task) -> bool {
+        self.matches_text(task)
+            && self
+                .label
+                .is_none_or(|label_id| task.labels.iter().any(|label| label.id == label_id))
+            && self
+                .priority
+                .is_none_or(|priority| task.priority == priority)
+            && self.due.is_none_or(|due| Self::matches_due(due, task))
+    }
+
+    fn matches_text(&self, task: &Task) -> bool {
+        let needle = self.text.trim().to_lowercase();
+        if needle.is_empty() {
+            return true;
+        }
+        if let Some(ids) = &self.matching_ids {
+            return ids.contains(&task.id);
+        }
+        task.title.to_lowercase().contains(&needle)
+            || task
+                .description
+                .as_ref()
+                .is_some_and(|desc| desc.to_lowercase().contains(&needle))
+    }
+
+    fn matches_due(due: DueFilter, task: &Task) -> bool {
+        let today = Local::now().date_naive();
+        match (due, task.due_date) {
+            (DueFilter::NoDueDate, due_date) => due_date.is_none(),
+            (_, None) => false,
+            (DueFilter::Overdue, Some(date)) => date < today,
+            (DueFilter::DueToday, Some(date)) => date == today,
+            (DueFilter::DueThisWeek, Some(date)) => date >= today && date <= today + Days::new(7),
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use crate::model::Task;
+
+const MAX_HISTORY: usize = 100;
+
+// A board mutation, stored as the affected tasks before and after it was made.
+// Tasks missing from `before` were created and tasks missing from `after` were deleted.
+#[derive(Clone, Debug, Default)]
+pub struct Change {
+    pub before: Vec<Task>,
+    pub after: Vec<Task>,
+}
+
+#[derive(Default)]
+pub struct History {
+    undo: Vec<Change>,
+    redo: Vec<Change>,
+}
+
+impl History {
+    pub fn record(&mut self, change: Change) {
+        self.redo.clear();
+        self.undo.push(change);
+        if self.undo.len() > MAX_HISTORY {
+            self.undo.remove(0);
+        }
+    }
+
+    pub fn undo(&mut self) -> Option<Change> {
+        let change = self.undo.pop()?;
+        self.redo.push(change.clone());
+        Some(change)
+    }
+
+    pub fn redo(&mut self) -> Option<Change> {
+        let change = self.redo.pop()?;
+        self.undo.push(change.clone());
+        Some(change)
+    }
+
+    // Drops every change touching a task that no longer exists, so undo can't bring it back
+    pub fn forget(&mut self, task_id: i64) {
+        let touches = |change: &Change| {
+            change
+                .before
+                .iter()
+                .chain(&change.after)
+                .any(|task| task.id == task_id)
+        };
+        self.undo.retain(|change| !touches(change));
+        self.redo.retain(|change| !touches(change));
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+}
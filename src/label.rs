@@ -0,0 +1,88 @@
+use std::fmt;
+
+use iced::Color;
+use iced::futures::TryFutureExt;
+use sqlx::{Pool, Sqlite};
+
+use crate::error::Error;
+
+pub const COLORS: [&str; 8] = [
+    "#e06c75", "#98c379", "#e5c07b", "#61afef", "#c678dd", "#56b6c2", "#d19a66", "#abb2bf",
+];
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Label {
+    pub id: i64,
+    pub name: String,
+    pub color: String,
+}
+
+impl fmt::Display for Label {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+impl Label {
+    pub fn color(&self) -> Color {
+        Color::parse(&self.color).unwrap_or(Color::from_rgb8(0xab, 0xb2, 0xbf))
+    }
+}
+
+pub async fn get_labels(pool: Pool<Sqlite>) -> Result<Vec<Label>, Error> {
+    sqlx::query_as!(
+        Label,
+        r#"SELECT id as "id!", name, color FROM labels ORDER BY name"#
+    )
+    .fetch_all(&pool)
+    .map_err(Error::db("loading labels"))
+    .await
+}
+
+pub async fn insert_label(pool: Pool<Sqlite>, name: String) -> Result<Label, Error> {
+    // Cycle through the palette so neighbouring labels get distinct colours
+    let count = sqlx::query_scalar!("SELECT COUNT(*) FROM labels")
+        .fetch_one(&pool)
+        .map_err(Error::db("counting labels"))
+        .await?;
+    let color = COLORS[count as usize % COLORS.len()].to_string();
+
+    let id = sqlx::query!(
+        "INSERT INTO labels (name, color) VALUES (?, ?)",
+        name,
+        color
+    )
+    .execute(&pool)
+    .map_err(Error::db("inserting label into db"))
+    .map_ok(|res| res.last_insert_rowid())
+    .await?;
+    Ok(Label { id, name, color })
+}
+
+pub async fn set_task_labels(
+    pool: Pool<Sqlite>,
+    task_id: i64,
+    label_ids: Vec<i64>,
+) -> Result<(), Error> {
+    let mut tx = pool
+        .begin()
+        .map_err(Error::db("starting transaction"))
+        .await?;
+    sqlx::query!("DELETE FROM task_labels WHERE task_id = ?", task_id)
+        .execute(&mut *tx)
+        .map_err(Error::db("clearing task labels"))
+        .await?;
+    for label_id in label_ids {
+        sqlx::query!(
+            "INSERT INTO task_labels (task_id, label_id) VALUES (?, ?)",
+            task_id,
+            label_id
+        )
+        .execute(&mut *tx)
+        .map_err(Error::db("adding task label"))
+        .await?;
+    }
+    tx.commit()
+        .map_err(Error::db("committing task labels"))
+        .await
+}
@@ -0,0 +1,349 @@
+use std::collections::HashMap;
+
+use iced::alignment::Horizontal;
+use iced::widget::{Column, button, column, container, row, text, text_input};
+use iced::{Element, Length};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(from = "LaneSpec", into = "LaneSpec")]
+pub struct Lane {
+    pub name: String,
+    pub wip_limit: Option<u32>,
+}
+
+// Lanes without a WIP limit are written as plain names, the format used by
+// configs from before limits existed.
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+enum LaneSpec {
+    Name(String),
+    Limited {
+        name: String,
+        #[serde(default)]
+        wip_limit: Option<u32>,
+    },
+}
+
+impl Lane {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            wip_limit: None,
+        }
+    }
+
+    /// Explains why another task can't be added to the lane, holding `count`
+    /// tasks already, without breaking its WIP limit.
+    pub fn wip_warning(&self, count: usize) -> Option<String> {
+        let limit = self.wip_limit?;
+        (count >= limit as usize)
+            .then(|| format!("\"{}\" is at its WIP limit of {limit}", self.name))
+    }
+}
+
+impl From<LaneSpec> for Lane {
+    fn from(spec: LaneSpec) -> Self {
+        match spec {
+            LaneSpec::Name(name) => Lane::new(name),
+            LaneSpec::Limited { name, wip_limit } => Lane { name, wip_limit },
+        }
+    }
+}
+
+impl From<Lane> for LaneSpec {
+    fn from(lane: Lane) -> Self {
+        match lane.wip_limit {
+            None => LaneSpec::Name(lane.name),
+            wip_limit => LaneSpec::Limited {
+                name: lane.name,
+                wip_limit,
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Message {
+    LaneNameUpdated(usize, String),
+    WipLimitUpdated(usize, String),
+    NewLaneNameUpdated(String),
+    AddLane,
+    RemoveLane(usize),
+    MoveUp(usize),
+    MoveDown(usize),
+    Save,
+    Cancel,
+}
+
+#[derive(Clone, Debug)]
+struct LaneEntry {
+    original: Option<String>,
+    name: String,
+    wip_limit: String,
+}
+
+pub struct LaneEditor {
+    lanes: Vec<LaneEntry>,
+    removed: Vec<String>,
+    new_lane_name: String,
+    error: Option<String>,
+}
+
+/// The outcome of a lane edit: the new lane list, the lanes that were renamed
+/// and where tasks in removed lanes should be moved.
+pub struct LaneChanges {
+    pub lanes: Vec<Lane>,
+    pub renames: HashMap<String, String>,
+    pub moves: HashMap<String, String>,
+}
+
+impl LaneEditor {
+    pub fn new(lanes: &[Lane]) -> Self {
+        Self {
+            lanes: lanes
+                .iter()
+                .map(|lane| LaneEntry {
+                    original: Some(lane.name.clone()),
+                    name: lane.name.clone(),
+                    wip_limit: lane.wip_limit.map(|l| l.to_string()).unwrap_or_default(),
+                })
+                .collect(),
+            removed: vec![],
+            new_lane_name: String::new(),
+            error: None,
+        }
+    }
+
+    pub fn update(&mut self, msg: Message) {
+        self.error = None;
+        match msg {
+            Message::LaneNameUpdated(idx, name) => {
+                if let Some(lane) = self.lanes.get_mut(idx) {
+                    lane.name = name;
+                }
+            }
+            Message::WipLimitUpdated(idx, wip_limit) => {
+                if let Some(lane) = self.lanes.get_mut(idx) {
+                    lane.wip_limit = wip_limit;
+                }
+            }
+            Message::NewLaneNameUpdated(name) => self.new_lane_name = name,
+            Message::AddLane => {
+                let name = self.new_lane_name.trim().to_string();
+                if !name.is_empty() {
+                    self.lanes.push(LaneEntry {
+                        original: None,
+                        name,
+                        wip_limit: String::new(),
+                    });
+                    self.new_lane_name.clear();
+                }
+            }
+            Message::RemoveLane(idx) => {
+                if idx < self.lanes.len()
+                    && let Some(original) = self.lanes.remove(idx).original
+                {
+                    self.removed.push(original);
+                }
+            }
+            Message::MoveUp(idx) => {
+                if idx > 0 && idx < self.lanes.len() {
+                    self.lanes.swap(idx - 1, idx);
+                }
+            }
+            Message::MoveDown(idx) => {
+                if idx + 1 < self.lanes.len() {
+                    self.lanes.swap(idx, idx + 1);
+                }
+            }
+            Message::Save | Message::Cancel => {}
+        }
+    }
+
+    /// Validates the edited lanes, recording the error for display if they
+    /// cannot be saved.
+    pub fn changes(&mut self) -> Option<LaneChanges> {
+        match self.validate() {
+            Ok(changes) => Some(changes),
+            Err(err) => {
+                self.error = Some(err);
+                None
+            }
+        }
+    }
+
+    fn validate(&self) -> Result<LaneChanges, String> {
+        let lanes: Vec<String> = self
+            .lanes
+            .iter()
+            .map(|lane| lane.name.trim().to_string())
+            .collect();
+
+        if lanes.is_empty() {
+            return Err("A board needs at least one lane".into());
+        }
+        if lanes.iter().any(|lane| lane.is_empty()) {
+            return Err("Lane names cannot be empty".into());
+        }
+        for (idx, lane) in lanes.iter().enumerate() {
+            if lanes[..idx].contains(lane) {
+                return Err(format!("Lane \"{lane}\" is listed more than once"));
+            }
+        }
+
+        let mut renames = HashMap::new();
+        for (entry, name) in self.lanes.iter().zip(&lanes) {
+            if let Some(original) = &entry.original
+                && original != name
+            {
+                renames.insert(original.clone(), name.clone());
+            }
+        }
+        // Tasks in removed lanes are moved into the first lane
+        let mut moves = HashMap::new();
+        for removed in self.removed.iter() {
+            moves.insert(removed.clone(), lanes[0].clone());
+        }
+
+        let mut limited = vec![];
+        for (entry, name) in self.lanes.iter().zip(lanes) {
+            let wip_limit = match entry.wip_limit.trim() {
+                "" => None,
+                limit => match limit.parse::<u32>() {
+                    Ok(limit) if limit > 0 => Some(limit),
+                    _ => {
+                        return Err(format!(
+                            "WIP limit for \"{name}\" must be a positive number"
+                        ));
+                    }
+                },
+            };
+            limited.push(Lane { name, wip_limit });
+        }
+        Ok(LaneChanges {
+            lanes: limited,
+            renames,
+            moves,
+        })
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let count = self.lanes.len();
+        let lanes = self.lanes.iter().enumerate().map(|(idx, lane)| {
+            row![
+                text_input("Lane name", &lane.name)
+                    .on_input(move |name| Message::LaneNameUpdated(idx, name)),
+                text_input("WIP", &lane.wip_limit)
+                    .on_input(move |limit| Message::WipLimitUpdated(idx, limit))
+                    .width(Length::Fixed(60.0)),
+                button("^").on_press_maybe((idx > 0).then_some(Message::MoveUp(idx))),
+                button("v").on_press_maybe((idx + 1 < count).then_some(Message::MoveDown(idx))),
+                button("X").on_press(Message::RemoveLane(idx)),
+            ]
+            .spacing(4)
+            .into()
+        });
+
+        let mut content = column![
+            text("Lanes").size(24),
+            Column::with_children(lanes).spacing(4)
+        ]
+        .spacing(8)
+        .align_x(Horizontal::Center);
+
+        content = content.push(
+            row![
+                text_input("New lane", &self.new_lane_name)
+                    .on_input(Message::NewLaneNameUpdated)
+                    .on_submit(Message::AddLane),
+                button("Add").on_press(Message::AddLane),
+            ]
+            .spacing(4),
+        );
+        if let Some(err) = &self.error {
+            content = content.push(text(err));
+        }
+        content = content.push(
+            row![
+                button("Save").on_press(Message::Save),
+                button("Cancel").on_press(Message::Cancel)
+            ]
+            .spacing(8),
+        );
+
+        container(content)
+            .style(container::bordered_box)
+            .padding([16, 16])
+            .width(400)
+            .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // TOML has no top-level arrays, so lanes are parsed the way the config holds them
+    #[derive(Debug, Deserialize, Serialize)]
+    struct Lanes {
+        lanes: Vec<Lane>,
+    }
+
+    fn parse(toml: &str) -> Vec<Lane> {
+        toml::from_str::<Lanes>(toml).unwrap().lanes
+    }
+
+    fn limited(name: &str, wip_limit: u32) -> Lane {
+        Lane {
+            name: name.into(),
+            wip_limit: Some(wip_limit),
+        }
+    }
+
+    #[test]
+    fn plain_names_are_lanes_without_limits() {
+        assert_eq!(
+            parse(r#"lanes = ["To do", "Done"]"#),
+            [Lane::new("To do"), Lane::new("Done")]
+        );
+    }
+
+    #[test]
+    fn tables_carry_wip_limits() {
+        let lanes = parse(
+            r#"
+            [[lanes]]
+            name = "Doing"
+            wip_limit = 3
+
+            [[lanes]]
+            name = "Done"
+            "#,
+        );
+        assert_eq!(lanes, [limited("Doing", 3), Lane::new("Done")]);
+    }
+
+    #[test]
+    fn names_and_tables_can_be_mixed() {
+        let lanes = parse(r#"lanes = ["To do", { name = "Doing", wip_limit = 2 }, "Done"]"#);
+        assert_eq!(
+            lanes,
+            [Lane::new("To do"), limited("Doing", 2), Lane::new("Done")]
+        );
+    }
+
+    #[test]
+    fn lanes_without_limits_are_written_as_plain_names() {
+        let lanes = vec![Lane::new("To do"), limited("Doing", 2), Lane::new("Done")];
+        let written = toml::to_string(&Lanes {
+            lanes: lanes.clone(),
+        })
+        .unwrap();
+        assert_eq!(
+            written,
+            "lanes = [\"To do\", { name = \"Doing\", wip_limit = 2 }, \"Done\"]\n"
+        );
+        assert_eq!(parse(&written), lanes);
+    }
+}
@@ -1,10 +1,21 @@
-use crate::task::Task;
-use iced::alignment::Horizontal;
+use std::cmp::Ordering;
+use std::rc::Rc;
+
+use crate::error::Error;
+use crate::event::TaskEvent;
+use crate::label::Label;
+use crate::model::{MATCH_END, MATCH_START, Priority, Task, TaskRef};
+use crate::subtask::Subtask;
+use chrono::{Datelike, Days, Local, Months, NaiveDate};
+use iced::alignment::{Horizontal, Vertical};
+use iced::widget::markdown::{self, Url};
 use iced::widget::{
-    button, center, column, container, mouse_area, opaque, row, stack, text, text_editor,
-    text_input,
+    Column, Row, Space, button, center, checkbox, column, container, mouse_area, opaque, pick_list,
+    rich_text, row, scrollable, span, stack, text, text_editor, text_input,
 };
-use iced::{Color, Element, Length};
+use iced::{Border, Color, Element, Font, Length, Theme, font, mouse};
+
+pub const THEME: Theme = Theme::KanagawaDragon;
 
 pub fn modal<'a, Message>(
     base: impl Into<Element<'a, Message>>,
@@ -37,89 +48,517 @@ where
 
 pub fn swim_lane<'a, Message>(
     title: String,
+    over_limit: bool,
     tasks: Vec<Element<'a, Message>>,
+    is_drop_target: bool,
+    drag_enter: Message,
+    drag_leave: Message,
+    drop: Message,
 ) -> Element<'a, Message>
 where
     Message: Clone + 'a,
 {
-    let lane_title = text(title).size(24);
+    let lane_title = text(title).size(24).style(move |theme: &Theme| {
+        if over_limit {
+            text::danger(theme)
+        } else {
+            text::Style::default()
+        }
+    });
     let mut content = column!(lane_title).spacing(8).width(Length::Fill);
 
     for task in tasks {
         content = content.push(task);
     }
 
-    content.into()
+    let lane = container(content)
+        .padding(4)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .style(move |theme: &Theme| {
+            if is_drop_target {
+                let palette = theme.extended_palette();
+                container::Style {
+                    background: Some(palette.background.weak.color.into()),
+                    border: Border {
+                        color: palette.primary.strong.color,
+                        width: 2.0,
+                        radius: 4.0.into(),
+                    },
+                    ..container::Style::default()
+                }
+            } else {
+                container::Style::default()
+            }
+        });
+
+    mouse_area(lane)
+        .on_enter(drag_enter)
+        .on_exit(drag_leave)
+        .on_release(drop)
+        .into()
+}
+
+pub struct CardActions<Message> {
+    pub archive: Message,
+    pub grab: Message,
+    pub release: Message,
+    pub move_up: Option<Message>,
+    pub move_down: Option<Message>,
+    pub next_lane: Option<Message>,
 }
 
 pub fn task_card<'a, Message>(
     task: &'a Task,
-    remove: Message,
-    open_modal: Message,
-    next_lane: Option<Message>,
+    is_dragged: bool,
+    is_blocked: bool,
+    actions: CardActions<Message>,
 ) -> Element<'a, Message>
 where
     Message: Clone + 'a,
 {
     let card_content = row![
         column![
-            text(&task.title).size(20),
+            row![
+                text(&task.title).size(20).width(Length::Fill),
+                blocked_badge(is_blocked),
+                priority_badge(task.priority),
+            ]
+            .spacing(4),
             text(task.id),
-            button(">").on_press_maybe(next_lane),
+            Row::with_children(task.labels.iter().map(label_chip))
+                .spacing(4)
+                .wrap(),
+            text(
+                task.due_date
+                    .map(|date| format!("Due {}", date.format("%Y-%m-%d")))
+                    .unwrap_or_default()
+            ),
+            text(if task.subtasks.is_empty() {
+                String::new()
+            } else {
+                checklist_progress(&task.subtasks)
+            }),
+            row![
+                button("^").on_press_maybe(actions.move_up),
+                button("v").on_press_maybe(actions.move_down),
+                button(">").on_press_maybe(actions.next_lane),
+            ]
+            .spacing(4),
         ]
         .width(Length::Fill),
-        button("X").on_press(remove)
+        button("X").on_press(actions.archive)
     ];
 
+    // Overdue cards compare Less, cards due today compare Equal
+    let due_status = task
+        .due_date
+        .map(|date| date.cmp(&Local::now().date_naive()));
+
     let card = container(card_content)
-        .style(container::rounded_box)
+        .style(move |theme: &Theme| {
+            let palette = theme.extended_palette();
+            let style = match due_status {
+                Some(Ordering::Less) => container::rounded_box(theme)
+                    .background(palette.danger.weak.color)
+                    .color(palette.danger.weak.text),
+                Some(Ordering::Equal) => container::rounded_box(theme)
+                    .background(palette.secondary.strong.color)
+                    .color(palette.secondary.strong.text),
+                _ => container::rounded_box(theme),
+            };
+            if is_dragged {
+                style.border(Border {
+                    color: palette.primary.base.color,
+                    width: 1.0,
+                    radius: 4.0.into(),
+                })
+            } else {
+                style
+            }
+        })
         .padding(8)
         .width(Length::Fill);
 
-    mouse_area(card).on_press(open_modal).into()
+    mouse_area(card)
+        .on_press(actions.grab)
+        .on_release(actions.release)
+        .interaction(if is_dragged {
+            mouse::Interaction::Grabbing
+        } else {
+            mouse::Interaction::Grab
+        })
+        .into()
+}
+
+pub struct TaskForm<'a, Message> {
+    pub modal_title: String,
+    pub title: &'a str,
+    pub description: &'a text_editor::Content,
+    pub title_update: &'a dyn Fn(String) -> Message,
+    pub description_update: &'a dyn Fn(text_editor::Action) -> Message,
+    pub preview: Option<Element<'a, Message>>,
+    pub toggle_preview: &'a dyn Fn(bool) -> Message,
+    pub fields: Vec<(&'static str, Element<'a, Message>)>,
+    pub warning: Option<&'a str>,
+    pub submit: Message,
+    pub cancel: Message,
 }
 
-pub fn task_dialog_mut<'a, Message, TU, DU>(
-    modal_title: String,
-    task_title: &'a str,
-    task_description: &'a text_editor::Content,
-    title_update: &'a TU,
-    description_update: &'a DU,
-    submit: Message,
-    cancel: Message,
+pub fn priority_badge<'a, Message>(priority: Priority) -> Element<'a, Message>
+where
+    Message: 'a,
+{
+    container(text(priority.to_string()).size(12))
+        .padding([2, 6])
+        .style(move |theme: &Theme| {
+            let palette = theme.extended_palette();
+            let pair = match priority {
+                Priority::Low => palette.background.strong,
+                Priority::Normal => palette.secondary.base,
+                Priority::High => palette.primary.strong,
+                Priority::Urgent => palette.danger.strong,
+            };
+            container::Style::default()
+                .background(pair.color)
+                .color(pair.text)
+                .border(Border {
+                    radius: 8.0.into(),
+                    ..Border::default()
+                })
+        })
+        .into()
+}
+
+fn blocked_badge<'a, Message>(is_blocked: bool) -> Element<'a, Message>
+where
+    Message: 'a,
+{
+    if !is_blocked {
+        return Space::new(Length::Shrink, Length::Shrink).into();
+    }
+    container(text("Blocked").size(12))
+        .padding([2, 6])
+        .style(|theme: &Theme| {
+            let pair = theme.extended_palette().danger.base;
+            container::Style::default()
+                .background(pair.color)
+                .color(pair.text)
+                .border(Border {
+                    radius: 8.0.into(),
+                    ..Border::default()
+                })
+        })
+        .into()
+}
+
+pub fn label_chip<'a, Message>(label: &'a Label) -> Element<'a, Message>
+where
+    Message: 'a,
+{
+    let color = label.color();
+    container(text(&label.name).size(12))
+        .padding([2, 6])
+        .style(move |_theme: &Theme| {
+            container::Style::default()
+                .background(color)
+                .color(Color::BLACK)
+                .border(Border {
+                    radius: 8.0.into(),
+                    ..Border::default()
+                })
+        })
+        .into()
+}
+
+pub fn label_picker<'a, Message>(
+    labels: &'a [Label],
+    selected: &[i64],
+    toggle: impl Fn(i64, bool) -> Message + 'a,
+    new_label_name: &'a str,
+    new_label_update: impl Fn(String) -> Message + 'a,
+    create: Message,
 ) -> Element<'a, Message>
 where
     Message: Clone + 'a,
-    TU: Fn(String) -> Message + 'a,
-    DU: Fn(text_editor::Action) -> Message + 'a,
 {
-    let content = column![
-        text(modal_title).size(24),
-        text("Title"),
-        text_input("", task_title)
-            .on_input(title_update)
-            .on_paste(title_update),
-        text("Description"),
-        text_editor(task_description)
-            .height(Length::Fill)
-            .on_action(description_update),
+    let toggle = Rc::new(toggle);
+    let chips = labels.iter().map(|label| {
+        let toggle = toggle.clone();
+        let label_id = label.id;
         row![
-            button("Submit").on_press(submit),
-            button("Cancel").on_press(cancel)
+            checkbox("", selected.contains(&label_id)).on_toggle(move |on| toggle(label_id, on)),
+            label_chip(label),
         ]
-        .spacing(8)
+        .align_y(Vertical::Center)
+        .into()
+    });
+
+    column![
+        Row::with_children(chips).spacing(8).wrap(),
+        row![
+            text_input("New label", new_label_name)
+                .on_input(new_label_update)
+                .on_submit(create.clone()),
+            button("Add").on_press(create),
+        ]
+        .spacing(4),
+    ]
+    .spacing(4)
+    .into()
+}
+
+pub fn dependency_picker<'a, Message>(
+    blockers: Vec<TaskRef>,
+    options: Vec<TaskRef>,
+    pick: impl Fn(TaskRef) -> Message + 'a,
+    remove: impl Fn(i64) -> Message + 'a,
+) -> Element<'a, Message>
+where
+    Message: Clone + 'a,
+{
+    let chips = blockers.into_iter().map(|blocker| {
+        row![
+            text(blocker.to_string()),
+            button("X").style(button::text).on_press(remove(blocker.id)),
+        ]
+        .align_y(Vertical::Center)
+        .into()
+    });
+    column![
+        Row::with_children(chips).spacing(8).wrap(),
+        pick_list(options, None::<TaskRef>, pick).placeholder("Add blocking task"),
+    ]
+    .spacing(4)
+    .into()
+}
+
+fn checklist_progress(subtasks: &[Subtask]) -> String {
+    let done = subtasks.iter().filter(|subtask| subtask.done).count();
+    format!("Checklist {}/{}", done, subtasks.len())
+}
+
+pub fn checklist_editor<'a, Message>(
+    subtasks: &'a [Subtask],
+    toggle: impl Fn(i64, bool) -> Message + 'a,
+    remove: impl Fn(i64) -> Message + 'a,
+    new_title: &'a str,
+    new_title_update: impl Fn(String) -> Message + 'a,
+    add: Message,
+) -> Element<'a, Message>
+where
+    Message: Clone + 'a,
+{
+    let toggle = Rc::new(toggle);
+    let items = subtasks.iter().map(|subtask| {
+        let toggle = toggle.clone();
+        let subtask_id = subtask.id;
+        row![
+            checkbox(&subtask.title, subtask.done)
+                .on_toggle(move |done| toggle(subtask_id, done))
+                .width(Length::Fill),
+            button("X").style(button::text).on_press(remove(subtask_id)),
+        ]
+        .align_y(Vertical::Center)
+        .into()
+    });
+
+    column![
+        Column::with_children(items).spacing(4),
+        row![
+            text_input("New item", new_title)
+                .on_input(new_title_update)
+                .on_submit(add.clone()),
+            button("Add").on_press(add),
+        ]
+        .spacing(4),
+    ]
+    .spacing(4)
+    .into()
+}
+
+// Renders text containing MATCH_START/MATCH_END markers with the matched
+// parts emphasised.
+pub fn highlighted_text<'a, Message>(marked: &'a str) -> Element<'a, Message>
+where
+    Message: Clone + 'static,
+{
+    let bold = Font {
+        weight: font::Weight::Bold,
+        ..Font::default()
+    };
+    let mut spans = vec![];
+    for (idx, part) in marked.split(MATCH_START).enumerate() {
+        // Every part after the first starts with a match
+        match part.split_once(MATCH_END) {
+            Some((matched, rest)) if idx > 0 => {
+                spans.push(span(matched).font(bold).underline(true));
+                spans.push(span(rest));
+            }
+            _ => spans.push(span(part)),
+        }
+    }
+    rich_text(spans).into()
+}
+
+pub fn search_result<'a, Message>(
+    title: &'a str,
+    snippet: &'a str,
+    open: Message,
+) -> Element<'a, Message>
+where
+    Message: Clone + 'static,
+{
+    let mut content = column![highlighted_text(title)].spacing(2);
+    if !snippet.is_empty() {
+        content = content.push(highlighted_text(snippet));
+    }
+    button(content)
+        .style(button::secondary)
+        .width(Length::Fill)
+        .on_press(open)
+        .into()
+}
+
+pub fn task_dialog_mut<'a, Message>(form: TaskForm<'a, Message>) -> Element<'a, Message>
+where
+    Message: Clone + 'a,
+{
+    let mut content = column![
+        text(form.modal_title).size(24),
+        text("Title"),
+        text_input("", form.title)
+            .on_input(form.title_update)
+            .on_paste(form.title_update),
+        row![
+            text("Description").width(Length::Fill),
+            checkbox("Preview", form.preview.is_some()).on_toggle(form.toggle_preview),
+        ],
+        match form.preview {
+            Some(preview) => container(scrollable(preview)).height(Length::Fill).into(),
+            None => Element::from(
+                text_editor(form.description)
+                    .height(Length::Fill)
+                    .on_action(form.description_update),
+            ),
+        },
     ]
     .spacing(8)
     .align_x(Horizontal::Center);
 
+    for (label, field) in form.fields {
+        content = content.push(text(label)).push(field);
+    }
+    if let Some(warning) = form.warning {
+        content = content.push(text(warning).style(text::danger));
+    }
+
+    content = content.push(
+        row![
+            button("Submit").on_press(form.submit),
+            button("Cancel").on_press(form.cancel)
+        ]
+        .spacing(8),
+    );
+
     container(content)
         .style(container::bordered_box)
         .padding([16, 16])
         .into()
 }
 
+pub fn markdown_text<'a, Message>(
+    items: &'a [markdown::Item],
+    on_link: impl Fn(Url) -> Message + 'a,
+) -> Element<'a, Message>
+where
+    Message: 'a,
+{
+    markdown::view(
+        items,
+        markdown::Settings::default(),
+        markdown::Style::from_palette(THEME.palette()),
+    )
+    .map(on_link)
+}
+
+pub fn date_picker<'a, Message>(
+    selected: Option<NaiveDate>,
+    month: NaiveDate,
+    pick: impl Fn(Option<NaiveDate>) -> Message + 'a,
+    change_month: impl Fn(NaiveDate) -> Message + 'a,
+) -> Element<'a, Message>
+where
+    Message: Clone + 'a,
+{
+    let month = month.with_day(1).unwrap_or(month);
+    let header = row![
+        button("<").on_press_maybe(month.checked_sub_months(Months::new(1)).map(&change_month)),
+        container(text(month.format("%B %Y").to_string()))
+            .align_x(Horizontal::Center)
+            .width(Length::Fill),
+        button(">").on_press_maybe(month.checked_add_months(Months::new(1)).map(&change_month)),
+    ]
+    .align_y(Vertical::Center);
+
+    let weekdays = row(["Mo", "Tu", "We", "Th", "Fr", "Sa", "Su"].map(|day| {
+        container(text(day))
+            .align_x(Horizontal::Center)
+            .width(Length::Fill)
+            .into()
+    }));
+
+    let mut weeks = column![header, weekdays].spacing(2);
+    let mut day = month - Days::new(month.weekday().num_days_from_monday().into());
+    while day.month() == month.month() || day < month {
+        let mut week = row![].spacing(2);
+        for _ in 0..7 {
+            let cell: Element<'a, Message> = if day.month() == month.month() {
+                let style = if Some(day) == selected {
+                    button::primary
+                } else {
+                    button::text
+                };
+                button(
+                    container(text(day.day()))
+                        .align_x(Horizontal::Center)
+                        .width(Length::Fill),
+                )
+                .style(style)
+                .width(Length::Fill)
+                .on_press(pick(Some(day)))
+                .into()
+            } else {
+                Space::with_width(Length::Fill).into()
+            };
+            week = week.push(cell);
+            day = day + Days::new(1);
+        }
+        weeks = weeks.push(week);
+    }
+
+    let selected_text = selected
+        .map(|date| date.format("%Y-%m-%d").to_string())
+        .unwrap_or_else(|| "No due date".into());
+    weeks = weeks.push(
+        row![
+            text(selected_text).width(Length::Fill),
+            button("Today").on_press(pick(Some(Local::now().date_naive()))),
+            button("Clear").on_press(pick(None)),
+        ]
+        .spacing(4)
+        .align_y(Vertical::Center),
+    );
+
+    container(weeks).width(Length::Fixed(320.0)).into()
+}
+
 pub fn task_dialog<'a, Message>(
     task: &'a Task,
+    events: &'a [TaskEvent],
+    description: Element<'a, Message>,
+    toggle_subtask: impl Fn(i64, bool) -> Message + 'a,
+    dependencies: Vec<(&'static str, Vec<(String, Message)>)>,
     edit: Message,
     close: Message,
 ) -> Element<'a, Message>
@@ -134,13 +573,216 @@ where
             .width(Length::Fill),
         container(row![edit_button, close_button].spacing(4)).align_x(Horizontal::Right)
     ];
-    let content = if let Some(desc) = &task.description {
-        column![title_row, text(desc)]
-    } else {
-        column![title_row]
-    };
+    let mut content = column![title_row].spacing(8);
+    if let Some(due_date) = task.due_date {
+        content = content.push(text(format!("Due {}", due_date.format("%Y-%m-%d"))));
+    }
+    content = content.push(description);
+    if !task.subtasks.is_empty() {
+        let toggle = Rc::new(toggle_subtask);
+        let items = task.subtasks.iter().map(|subtask| {
+            let toggle = toggle.clone();
+            let subtask_id = subtask.id;
+            checkbox(&subtask.title, subtask.done)
+                .on_toggle(move |done| toggle(subtask_id, done))
+                .into()
+        });
+        content = content.push(text(checklist_progress(&task.subtasks)).size(16));
+        content = content.push(Column::with_children(items).spacing(4));
+    }
+    for (heading, links) in dependencies {
+        if links.is_empty() {
+            continue;
+        }
+        let links = links.into_iter().map(|(title, open)| {
+            button(text(title))
+                .style(button::text)
+                .on_press(open)
+                .into()
+        });
+        content = content.push(text(heading).size(16));
+        content = content.push(Column::with_children(links));
+    }
+    content = content.push(
+        text(format!(
+            "Created {} - updated {}",
+            task.created_at
+                .with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M"),
+            task.updated_at
+                .with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M")
+        ))
+        .size(12),
+    );
+    if !events.is_empty() {
+        let timeline = events.iter().map(|event| {
+            row![
+                text(
+                    event
+                        .occurred_at
+                        .with_timezone(&Local)
+                        .format("%Y-%m-%d %H:%M")
+                        .to_string()
+                )
+                .size(12)
+                .width(Length::Fixed(120.0)),
+                text(event.to_string()).size(12),
+            ]
+            .into()
+        });
+        content = content.push(text("History").size(16));
+        content = content.push(
+            container(scrollable(Column::with_children(timeline).spacing(2))).max_height(160),
+        );
+    }
     container(content)
         .style(container::bordered_box)
         .padding([16, 16])
         .into()
 }
+
+pub fn warning_banner<'a, Message>(warning: &'a str, dismiss: Message) -> Element<'a, Message>
+where
+    Message: Clone + 'a,
+{
+    container(
+        row![
+            text(warning).width(Length::Fill),
+            button("Dismiss").style(button::text).on_press(dismiss),
+        ]
+        .spacing(8)
+        .align_y(Vertical::Center),
+    )
+    .padding([4, 8])
+    .style(danger_box)
+    .into()
+}
+
+fn danger_box(theme: &Theme) -> container::Style {
+    let palette = theme.extended_palette();
+    container::Style::default()
+        .background(palette.danger.weak.color)
+        .color(palette.danger.weak.text)
+        .border(Border {
+            radius: 4.0.into(),
+            ..Border::default()
+        })
+}
+
+// Failures stack up in the bottom right corner until they're dismissed
+pub fn notifications<'a, Message>(
+    base: impl Into<Element<'a, Message>>,
+    errors: &'a [Error],
+    dismiss: impl Fn(usize) -> Message,
+) -> Element<'a, Message>
+where
+    Message: Clone + 'a,
+{
+    if errors.is_empty() {
+        return base.into();
+    }
+    let toasts = errors.iter().enumerate().map(|(idx, err)| {
+        container(
+            row![
+                text(err.to_string()).width(Length::Fill),
+                button("Dismiss").style(button::text).on_press(dismiss(idx)),
+            ]
+            .spacing(8)
+            .align_y(Vertical::Center),
+        )
+        .padding([8, 12])
+        .width(Length::Fixed(360.0))
+        .style(danger_box)
+        .into()
+    });
+    stack![
+        base.into(),
+        container(Column::with_children(toasts).spacing(8))
+            .padding(16)
+            .align_right(Length::Fill)
+            .align_bottom(Length::Fill),
+    ]
+    .into()
+}
+
+// Purging can't be undone, so the row asks again first. `cancel_purge` is only
+// given while it is asking.
+pub fn archived_task_row<'a, Message>(
+    task: &'a Task,
+    restore: Message,
+    purge: Message,
+    cancel_purge: Option<Message>,
+) -> Element<'a, Message>
+where
+    Message: Clone + 'a,
+{
+    let archived_at = task
+        .archived_at
+        .map(|at| {
+            at.with_timezone(&Local)
+                .format("Archived %Y-%m-%d %H:%M")
+                .to_string()
+        })
+        .unwrap_or_default();
+    let actions = match cancel_purge {
+        None => row![
+            button("Restore").on_press(restore),
+            button("Delete permanently")
+                .style(button::danger)
+                .on_press(purge),
+        ],
+        Some(cancel) => row![
+            text("Delete this task for good?"),
+            button("Delete").style(button::danger).on_press(purge),
+            button("Keep").on_press(cancel),
+        ],
+    };
+    let content = row![
+        column![
+            text(&task.title).size(20),
+            text(format!("{} - {}", task.lane, archived_at)).size(12),
+        ]
+        .width(Length::Fill),
+        actions.spacing(8).align_y(Vertical::Center),
+    ]
+    .spacing(8)
+    .align_y(Vertical::Center);
+
+    container(content)
+        .style(container::rounded_box)
+        .padding(8)
+        .width(Length::Fill)
+        .into()
+}
+
+// Horizontal bars scaled against the largest value, each row labelled on the
+// left and annotated with its formatted value on the right.
+pub fn bar_chart<'a, Message>(bars: Vec<(String, f64, String)>) -> Element<'a, Message>
+where
+    Message: 'a,
+{
+    const MAX_BAR_WIDTH: f32 = 300.0;
+    let max = bars.iter().map(|(_, value, _)| *value).fold(0.0, f64::max);
+    let rows = bars.into_iter().map(|(label, value, annotation)| {
+        let width = if max > 0.0 {
+            (value / max) as f32 * MAX_BAR_WIDTH
+        } else {
+            0.0
+        };
+        let bar = container(Space::new(Length::Fixed(width), Length::Fixed(16.0))).style(
+            |theme: &Theme| {
+                container::Style::default().background(theme.extended_palette().primary.base.color)
+            },
+        );
+        row![
+            text(label).width(Length::Fixed(160.0)),
+            bar,
+            text(annotation).size(12),
+        ]
+        .spacing(8)
+        .align_y(Vertical::Center)
+        .into()
+    });
+    Column::with_children(rows).spacing(4).into()
+}
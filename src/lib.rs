@@ -0,0 +1,22 @@
+//! Task boards stored in SQLite. The board window, command line and terminal UI
+//! are built on the same modules: `model` holds the task types and `repository`
+//! gives every front-end access to the task store.
+
+pub mod app;
+pub mod board;
+pub mod cfd;
+pub mod cli;
+pub mod dependency;
+pub mod error;
+pub mod event;
+pub mod filter;
+pub mod history;
+pub mod label;
+pub mod lane;
+pub mod layout;
+pub mod model;
+pub mod repository;
+pub mod stats;
+pub mod subtask;
+pub mod task;
+pub mod tui;
@@ -1,13 +1,19 @@
-use crate::app::App;
-
-mod app;
-mod layout;
-mod task;
+use todo_rs::app::App;
+use todo_rs::{cli, layout};
 
 fn main() -> iced::Result {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        if let Err(err) = cli::run(args) {
+            eprintln!("{err}");
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     iced::application("ToDo", App::update, App::view)
         .subscription(App::subscription)
-        .theme(|_| iced::Theme::KanagawaDragon)
+        .theme(|_| layout::THEME)
         .centered()
         .exit_on_close_request(false)
         .run_with(App::new)
@@ -0,0 +1,164 @@
+use std::fmt;
+
+use chrono::{DateTime, NaiveDate, Utc};
+
+use crate::label::Label;
+use crate::lane::Lane;
+use crate::subtask::Subtask;
+
+// Markers wrapped around matched terms in search highlights and snippets
+pub const MATCH_START: char = '\u{2}';
+pub const MATCH_END: char = '\u{3}';
+
+#[derive(Clone, Debug, Default)]
+pub struct Task {
+    pub id: i64,
+    pub title: String,
+    pub description: Option<String>,
+    pub lane: String,
+    pub position: i64,
+    pub due_date: Option<NaiveDate>,
+    pub priority: Priority,
+    pub labels: Vec<Label>,
+    pub subtasks: Vec<Subtask>,
+    pub blocked_by: Vec<i64>,
+    pub archived_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Task {
+    // Blockers that are still on the board and not yet in its final lane
+    pub fn open_blockers<'a>(&self, tasks: &'a [Task], lanes: &[Lane]) -> Vec<&'a Task> {
+        let final_lane = lanes.last().map(|lane| lane.name.as_str());
+        self.blocked_by
+            .iter()
+            .filter_map(|id| tasks.iter().find(|task| task.id == *id))
+            .filter(|blocker| Some(blocker.lane.as_str()) != final_lane)
+            .collect()
+    }
+
+    // Blocked tasks may still move forward, but not without a warning. Moves
+    // back or within a lane never warn.
+    pub fn blocked_warning(&self, lane: &str, tasks: &[Task], lanes: &[Lane]) -> Option<String> {
+        let lane_index = |name: &str| lanes.iter().position(|l| l.name == name);
+        if lane_index(lane) <= lane_index(&self.lane) {
+            return None;
+        }
+        let blockers = self.open_blockers(tasks, lanes);
+        if blockers.is_empty() {
+            return None;
+        }
+        let titles: Vec<&str> = blockers.iter().map(|t| t.title.as_str()).collect();
+        Some(format!(
+            "\"{}\" is still blocked by {}",
+            self.title,
+            titles.join(", ")
+        ))
+    }
+}
+
+// The position that puts a task at the end of `lane`
+pub fn next_position(tasks: &[Task], lane: &str) -> i64 {
+    tasks
+        .iter()
+        .filter(|task| task.lane == lane)
+        .map(|task| task.position + 1)
+        .max()
+        .unwrap_or_default()
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, sqlx::Type)]
+#[repr(i64)]
+pub enum Priority {
+    Low = 0,
+    #[default]
+    Normal = 1,
+    High = 2,
+    Urgent = 3,
+}
+
+impl Priority {
+    pub const ALL: [Priority; 4] = [
+        Priority::Low,
+        Priority::Normal,
+        Priority::High,
+        Priority::Urgent,
+    ];
+}
+
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Priority::Low => "Low",
+            Priority::Normal => "Normal",
+            Priority::High => "High",
+            Priority::Urgent => "Urgent",
+        };
+        write!(f, "{name}")
+    }
+}
+
+// A task offered as a blocker in the edit dialog
+#[derive(Clone, Debug, PartialEq)]
+pub struct TaskRef {
+    pub id: i64,
+    pub title: String,
+}
+
+impl fmt::Display for TaskRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{} {}", self.id, self.title)
+    }
+}
+
+impl From<&Task> for TaskRef {
+    fn from(task: &Task) -> Self {
+        TaskRef {
+            id: task.id,
+            title: task.title.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SearchMatch {
+    pub task_id: i64,
+    pub title: String,
+    pub snippet: String,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct NewTask {
+    pub board_id: i64,
+    pub title: String,
+    pub description: Option<String>,
+    pub lane: String,
+    pub due_date: Option<NaiveDate>,
+    pub priority: Priority,
+    pub labels: Vec<Label>,
+    pub blocked_by: Vec<i64>,
+}
+
+impl NewTask {
+    pub fn new(
+        board_id: i64,
+        title: String,
+        description: Option<String>,
+        lane: String,
+        due_date: Option<NaiveDate>,
+        priority: Priority,
+        labels: Vec<Label>,
+    ) -> Self {
+        NewTask {
+            board_id,
+            title,
+            description,
+            lane,
+            due_date,
+            priority,
+            labels,
+            blocked_by: vec![],
+        }
+    }
+}
@@ -0,0 +1,1584 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use iced::futures::TryFutureExt;
+use sqlx::{Pool, Sqlite, Transaction};
+
+use crate::board::{self, Board, DEFAULT_BOARD};
+use crate::dependency;
+use crate::error::Error;
+use crate::event::{self, TaskEvent};
+use crate::label::{self, COLORS, Label};
+use crate::model::{NewTask, Priority, SearchMatch, Task};
+use crate::subtask::{self, Subtask};
+
+// Futures don't borrow the repository so they can be handed to iced as tasks
+pub trait TaskRepository: Clone + Send + Sync + 'static {
+    fn get_boards(&self) -> impl Future<Output = Result<Vec<Board>, Error>> + Send + use<Self>;
+
+    fn insert_board(
+        &self,
+        name: String,
+    ) -> impl Future<Output = Result<i64, Error>> + Send + use<Self>;
+
+    fn get_tasks(
+        &self,
+        board_id: i64,
+    ) -> impl Future<Output = Result<Vec<Task>, Error>> + Send + use<Self>;
+
+    fn get_archived_tasks(
+        &self,
+        board_id: i64,
+    ) -> impl Future<Output = Result<Vec<Task>, Error>> + Send + use<Self>;
+
+    fn insert_task(
+        &self,
+        task: NewTask,
+    ) -> impl Future<Output = Result<Task, Error>> + Send + use<Self>;
+
+    fn edit_task(&self, task: Task) -> impl Future<Output = Result<(), Error>> + Send + use<Self>;
+
+    fn archive_task(
+        &self,
+        task_id: i64,
+        archived_at: Option<DateTime<Utc>>,
+    ) -> impl Future<Output = Result<(), Error>> + Send + use<Self>;
+
+    fn remove_task(
+        &self,
+        task_id: i64,
+    ) -> impl Future<Output = Result<(), Error>> + Send + use<Self>;
+
+    fn update_task_positions(
+        &self,
+        tasks: Vec<Task>,
+    ) -> impl Future<Output = Result<(), Error>> + Send + use<Self>;
+
+    // Writes the `to` state of an undo/redo transition
+    fn apply_change(
+        &self,
+        board_id: i64,
+        from: Vec<Task>,
+        to: Vec<Task>,
+    ) -> impl Future<Output = Result<(), Error>> + Send + use<Self>;
+
+    // Returns how many tasks were archived
+    fn archive_stale_tasks(
+        &self,
+        board_id: i64,
+        lane: String,
+        days: u32,
+    ) -> impl Future<Output = Result<u64, Error>> + Send + use<Self>;
+
+    fn move_lanes(
+        &self,
+        board_id: i64,
+        renames: HashMap<String, String>,
+        moves: HashMap<String, String>,
+    ) -> impl Future<Output = Result<(), Error>> + Send + use<Self>;
+
+    fn search_tasks(
+        &self,
+        board_id: i64,
+        query: String,
+    ) -> impl Future<Output = Result<Vec<SearchMatch>, Error>> + Send + use<Self>;
+
+    fn get_labels(&self) -> impl Future<Output = Result<Vec<Label>, Error>> + Send + use<Self>;
+
+    fn insert_label(
+        &self,
+        name: String,
+    ) -> impl Future<Output = Result<Label, Error>> + Send + use<Self>;
+
+    fn set_task_labels(
+        &self,
+        task_id: i64,
+        label_ids: Vec<i64>,
+    ) -> impl Future<Output = Result<(), Error>> + Send + use<Self>;
+
+    fn set_task_dependencies(
+        &self,
+        task_id: i64,
+        blocked_by: Vec<i64>,
+    ) -> impl Future<Output = Result<(), Error>> + Send + use<Self>;
+
+    fn insert_subtask(
+        &self,
+        task_id: i64,
+        title: String,
+    ) -> impl Future<Output = Result<Subtask, Error>> + Send + use<Self>;
+
+    fn set_subtask_done(
+        &self,
+        subtask_id: i64,
+        done: bool,
+    ) -> impl Future<Output = Result<(), Error>> + Send + use<Self>;
+
+    fn remove_subtask(
+        &self,
+        subtask_id: i64,
+    ) -> impl Future<Output = Result<(), Error>> + Send + use<Self>;
+
+    fn get_task_events(
+        &self,
+        task_id: i64,
+    ) -> impl Future<Output = Result<Vec<TaskEvent>, Error>> + Send + use<Self>;
+
+    fn get_board_events(
+        &self,
+        board_id: i64,
+    ) -> impl Future<Output = Result<Vec<TaskEvent>, Error>> + Send + use<Self>;
+}
+
+#[derive(Clone, Debug)]
+pub struct SqliteTaskRepository {
+    pool: Pool<Sqlite>,
+}
+
+impl SqliteTaskRepository {
+    pub fn new(pool: Pool<Sqlite>) -> Self {
+        Self { pool }
+    }
+}
+
+impl TaskRepository for SqliteTaskRepository {
+    fn get_boards(&self) -> impl Future<Output = Result<Vec<Board>, Error>> + Send + use<> {
+        board::get_boards(self.pool.clone())
+    }
+
+    fn insert_board(
+        &self,
+        name: String,
+    ) -> impl Future<Output = Result<i64, Error>> + Send + use<> {
+        board::insert_board(self.pool.clone(), name)
+    }
+
+    fn get_tasks(
+        &self,
+        board_id: i64,
+    ) -> impl Future<Output = Result<Vec<Task>, Error>> + Send + use<> {
+        fetch_tasks(self.pool.clone(), board_id, false)
+    }
+
+    fn get_archived_tasks(
+        &self,
+        board_id: i64,
+    ) -> impl Future<Output = Result<Vec<Task>, Error>> + Send + use<> {
+        fetch_tasks(self.pool.clone(), board_id, true)
+    }
+
+    fn insert_task(
+        &self,
+        task: NewTask,
+    ) -> impl Future<Output = Result<Task, Error>> + Send + use<> {
+        insert_task(self.pool.clone(), task)
+    }
+
+    fn edit_task(&self, task: Task) -> impl Future<Output = Result<(), Error>> + Send + use<> {
+        edit_task(self.pool.clone(), task)
+    }
+
+    fn archive_task(
+        &self,
+        task_id: i64,
+        archived_at: Option<DateTime<Utc>>,
+    ) -> impl Future<Output = Result<(), Error>> + Send + use<> {
+        archive_task(self.pool.clone(), task_id, archived_at)
+    }
+
+    fn remove_task(&self, task_id: i64) -> impl Future<Output = Result<(), Error>> + Send + use<> {
+        remove_task(self.pool.clone(), task_id)
+    }
+
+    fn update_task_positions(
+        &self,
+        tasks: Vec<Task>,
+    ) -> impl Future<Output = Result<(), Error>> + Send + use<> {
+        update_task_positions(self.pool.clone(), tasks)
+    }
+
+    fn apply_change(
+        &self,
+        board_id: i64,
+        from: Vec<Task>,
+        to: Vec<Task>,
+    ) -> impl Future<Output = Result<(), Error>> + Send + use<> {
+        apply_change(self.pool.clone(), board_id, from, to)
+    }
+
+    fn archive_stale_tasks(
+        &self,
+        board_id: i64,
+        lane: String,
+        days: u32,
+    ) -> impl Future<Output = Result<u64, Error>> + Send + use<> {
+        archive_stale_tasks(self.pool.clone(), board_id, lane, days)
+    }
+
+    fn move_lanes(
+        &self,
+        board_id: i64,
+        renames: HashMap<String, String>,
+        moves: HashMap<String, String>,
+    ) -> impl Future<Output = Result<(), Error>> + Send + use<> {
+        move_lanes(self.pool.clone(), board_id, renames, moves)
+    }
+
+    fn search_tasks(
+        &self,
+        board_id: i64,
+        query: String,
+    ) -> impl Future<Output = Result<Vec<SearchMatch>, Error>> + Send + use<> {
+        search_tasks(self.pool.clone(), board_id, query)
+    }
+
+    fn get_labels(&self) -> impl Future<Output = Result<Vec<Label>, Error>> + Send + use<> {
+        label::get_labels(self.pool.clone())
+    }
+
+    fn insert_label(
+        &self,
+        name: String,
+    ) -> impl Future<Output = Result<Label, Error>> + Send + use<> {
+        label::insert_label(self.pool.clone(), name)
+    }
+
+    fn set_task_labels(
+        &self,
+        task_id: i64,
+        label_ids: Vec<i64>,
+    ) -> impl Future<Output = Result<(), Error>> + Send + use<> {
+        label::set_task_labels(self.pool.clone(), task_id, label_ids)
+    }
+
+    fn set_task_dependencies(
+        &self,
+        task_id: i64,
+        blocked_by: Vec<i64>,
+    ) -> impl Future<Output = Result<(), Error>> + Send + use<> {
+        dependency::set_task_dependencies(self.pool.clone(), task_id, blocked_by)
+    }
+
+    fn insert_subtask(
+        &self,
+        task_id: i64,
+        title: String,
+    ) -> impl Future<Output = Result<Subtask, Error>> + Send + use<> {
+        subtask::insert_subtask(self.pool.clone(), task_id, title)
+    }
+
+    fn set_subtask_done(
+        &self,
+        subtask_id: i64,
+        done: bool,
+    ) -> impl Future<Output = Result<(), Error>> + Send + use<> {
+        subtask::set_subtask_done(self.pool.clone(), subtask_id, done)
+    }
+
+    fn remove_subtask(
+        &self,
+        subtask_id: i64,
+    ) -> impl Future<Output = Result<(), Error>> + Send + use<> {
+        subtask::remove_subtask(self.pool.clone(), subtask_id)
+    }
+
+    fn get_task_events(
+        &self,
+        task_id: i64,
+    ) -> impl Future<Output = Result<Vec<TaskEvent>, Error>> + Send + use<> {
+        event::get_task_events(self.pool.clone(), task_id)
+    }
+
+    fn get_board_events(
+        &self,
+        board_id: i64,
+    ) -> impl Future<Output = Result<Vec<TaskEvent>, Error>> + Send + use<> {
+        event::get_board_events(self.pool.clone(), board_id)
+    }
+}
+
+// Keeps tasks in memory only, for front-ends and tests that don't need a database.
+// No task history is recorded, so events always come back empty.
+#[derive(Clone, Debug, Default)]
+pub struct InMemoryTaskRepository {
+    store: Arc<Mutex<Store>>,
+}
+
+#[derive(Debug, Default)]
+struct Store {
+    last_board_id: i64,
+    last_id: i64,
+    last_label_id: i64,
+    last_subtask_id: i64,
+    boards: Vec<Board>,
+    tasks: Vec<(i64, Task)>,
+    labels: Vec<Label>,
+    lane_entered_at: HashMap<i64, DateTime<Utc>>,
+}
+
+impl Store {
+    fn task_mut(&mut self, task_id: i64) -> Option<&mut Task> {
+        self.tasks
+            .iter_mut()
+            .map(|(_, task)| task)
+            .find(|task| task.id == task_id)
+    }
+
+    // Mirrors the lane_entered_at trigger, which auto-archiving relies on
+    fn set_lane(&mut self, task_id: i64, lane: String) {
+        if let Some(task) = self.task_mut(task_id)
+            && task.lane != lane
+        {
+            task.lane = lane;
+            self.lane_entered_at.insert(task_id, Utc::now());
+        }
+    }
+}
+
+impl InMemoryTaskRepository {
+    // Starts with the default board, like a freshly migrated database
+    pub fn new() -> Self {
+        let store = Store {
+            last_board_id: DEFAULT_BOARD,
+            boards: vec![Board {
+                id: DEFAULT_BOARD,
+                name: "Default".into(),
+            }],
+            ..Store::default()
+        };
+        Self {
+            store: Arc::new(Mutex::new(store)),
+        }
+    }
+
+    fn with_store<T>(&self, f: impl FnOnce(&mut Store) -> T) -> std::future::Ready<T> {
+        let mut store = self.store.lock().unwrap_or_else(PoisonError::into_inner);
+        std::future::ready(f(&mut store))
+    }
+
+    fn tasks(&self, board_id: i64, archived: bool) -> Vec<Task> {
+        let mut tasks: Vec<Task> = self
+            .store
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .tasks
+            .iter()
+            .filter(|(board, task)| *board == board_id && task.archived_at.is_some() == archived)
+            .map(|(_, task)| task.clone())
+            .collect();
+        tasks.sort_by_key(|task| (std::cmp::Reverse(task.archived_at), task.position, task.id));
+        tasks
+    }
+}
+
+impl TaskRepository for InMemoryTaskRepository {
+    fn get_boards(&self) -> impl Future<Output = Result<Vec<Board>, Error>> + Send + use<> {
+        self.with_store(|store| Ok(store.boards.clone()))
+    }
+
+    fn insert_board(
+        &self,
+        name: String,
+    ) -> impl Future<Output = Result<i64, Error>> + Send + use<> {
+        self.with_store(|store| {
+            store.last_board_id += 1;
+            store.boards.push(Board {
+                id: store.last_board_id,
+                name,
+            });
+            Ok(store.last_board_id)
+        })
+    }
+
+    fn get_tasks(
+        &self,
+        board_id: i64,
+    ) -> impl Future<Output = Result<Vec<Task>, Error>> + Send + use<> {
+        std::future::ready(Ok(self.tasks(board_id, false)))
+    }
+
+    fn get_archived_tasks(
+        &self,
+        board_id: i64,
+    ) -> impl Future<Output = Result<Vec<Task>, Error>> + Send + use<> {
+        std::future::ready(Ok(self.tasks(board_id, true)))
+    }
+
+    fn insert_task(&self, t: NewTask) -> impl Future<Output = Result<Task, Error>> + Send + use<> {
+        self.with_store(|store| {
+            store.last_id += 1;
+            let now = Utc::now();
+            let position = store
+                .tasks
+                .iter()
+                .filter(|(board, task)| *board == t.board_id && task.lane == t.lane)
+                .map(|(_, task)| task.position + 1)
+                .max()
+                .unwrap_or_default();
+            let task = Task {
+                id: store.last_id,
+                title: t.title,
+                description: t.description,
+                lane: t.lane,
+                position,
+                due_date: t.due_date,
+                priority: t.priority,
+                labels: t.labels,
+                subtasks: vec![],
+                blocked_by: t.blocked_by,
+                archived_at: None,
+                created_at: now,
+                updated_at: now,
+            };
+            store.tasks.push((t.board_id, task.clone()));
+            store.lane_entered_at.insert(task.id, now);
+            Ok(task)
+        })
+    }
+
+    fn edit_task(&self, task: Task) -> impl Future<Output = Result<(), Error>> + Send + use<> {
+        self.with_store(|store| {
+            store.set_lane(task.id, task.lane.clone());
+            if let Some(stored) = store.task_mut(task.id) {
+                *stored = Task {
+                    labels: stored.labels.clone(),
+                    subtasks: stored.subtasks.clone(),
+                    blocked_by: stored.blocked_by.clone(),
+                    archived_at: stored.archived_at,
+                    created_at: stored.created_at,
+                    updated_at: Utc::now(),
+                    ..task
+                };
+            }
+            Ok(())
+        })
+    }
+
+    fn archive_task(
+        &self,
+        task_id: i64,
+        archived_at: Option<DateTime<Utc>>,
+    ) -> impl Future<Output = Result<(), Error>> + Send + use<> {
+        self.with_store(|store| {
+            if let Some(task) = store.task_mut(task_id) {
+                task.archived_at = archived_at;
+            }
+            Ok(())
+        })
+    }
+
+    fn remove_task(&self, task_id: i64) -> impl Future<Output = Result<(), Error>> + Send + use<> {
+        self.with_store(|store| {
+            store.tasks.retain(|(_, task)| task.id != task_id);
+            store.lane_entered_at.remove(&task_id);
+            for (_, task) in &mut store.tasks {
+                task.blocked_by.retain(|id| *id != task_id);
+            }
+            Ok(())
+        })
+    }
+
+    fn update_task_positions(
+        &self,
+        tasks: Vec<Task>,
+    ) -> impl Future<Output = Result<(), Error>> + Send + use<> {
+        self.with_store(|store| {
+            for task in tasks {
+                store.set_lane(task.id, task.lane);
+                if let Some(stored) = store.task_mut(task.id) {
+                    stored.position = task.position;
+                }
+            }
+            Ok(())
+        })
+    }
+
+    fn apply_change(
+        &self,
+        board_id: i64,
+        from: Vec<Task>,
+        to: Vec<Task>,
+    ) -> impl Future<Output = Result<(), Error>> + Send + use<> {
+        self.with_store(|store| {
+            store.tasks.retain(|(_, task)| {
+                !from.iter().any(|t| t.id == task.id) || to.iter().any(|t| t.id == task.id)
+            });
+            for task in to {
+                store.last_id = store.last_id.max(task.id);
+                store.set_lane(task.id, task.lane.clone());
+                match store.task_mut(task.id) {
+                    Some(stored) => *stored = task,
+                    None => {
+                        store.lane_entered_at.insert(task.id, Utc::now());
+                        store.tasks.push((board_id, task));
+                    }
+                }
+            }
+            Ok(())
+        })
+    }
+
+    fn archive_stale_tasks(
+        &self,
+        board_id: i64,
+        lane: String,
+        days: u32,
+    ) -> impl Future<Output = Result<u64, Error>> + Send + use<> {
+        self.with_store(|store| {
+            let now = Utc::now();
+            let mut archived = 0;
+            for (board, task) in &mut store.tasks {
+                let entered = store.lane_entered_at.get(&task.id).copied();
+                if *board == board_id
+                    && task.lane == lane
+                    && task.archived_at.is_none()
+                    && entered.is_some_and(|at| (now - at).num_days() >= days as i64)
+                {
+                    task.archived_at = Some(now);
+                    archived += 1;
+                }
+            }
+            Ok(archived)
+        })
+    }
+
+    fn move_lanes(
+        &self,
+        board_id: i64,
+        renames: HashMap<String, String>,
+        moves: HashMap<String, String>,
+    ) -> impl Future<Output = Result<(), Error>> + Send + use<> {
+        self.with_store(|store| {
+            let mut moved = vec![];
+            for (board, task) in &mut store.tasks {
+                if *board != board_id {
+                    continue;
+                }
+                if let Some(name) = renames.get(&task.lane) {
+                    task.lane = name.clone();
+                } else if let Some(lane) = moves.get(&task.lane) {
+                    moved.push((task.id, lane.clone()));
+                }
+            }
+            for (task_id, lane) in moved {
+                store.set_lane(task_id, lane);
+            }
+            Ok(())
+        })
+    }
+
+    fn search_tasks(
+        &self,
+        board_id: i64,
+        query: String,
+    ) -> impl Future<Output = Result<Vec<SearchMatch>, Error>> + Send + use<> {
+        let terms: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+        let matches = if terms.is_empty() {
+            vec![]
+        } else {
+            self.tasks(board_id, false)
+                .into_iter()
+                .filter(|task| {
+                    let text = format!(
+                        "{} {}",
+                        task.title,
+                        task.description.as_deref().unwrap_or_default()
+                    )
+                    .to_lowercase();
+                    terms.iter().all(|term| text.contains(term))
+                })
+                .map(|task| SearchMatch {
+                    task_id: task.id,
+                    title: task.title,
+                    snippet: String::new(),
+                })
+                .collect()
+        };
+        std::future::ready(Ok(matches))
+    }
+
+    fn get_labels(&self) -> impl Future<Output = Result<Vec<Label>, Error>> + Send + use<> {
+        self.with_store(|store| {
+            let mut labels = store.labels.clone();
+            labels.sort_by(|a, b| a.name.cmp(&b.name));
+            Ok(labels)
+        })
+    }
+
+    fn insert_label(
+        &self,
+        name: String,
+    ) -> impl Future<Output = Result<Label, Error>> + Send + use<> {
+        self.with_store(|store| {
+            if store.labels.iter().any(|label| label.name == name) {
+                return Err(Error::Invalid(format!(
+                    "The label \"{name}\" already exists"
+                )));
+            }
+            store.last_label_id += 1;
+            let label = Label {
+                id: store.last_label_id,
+                name,
+                color: COLORS[store.labels.len() % COLORS.len()].to_string(),
+            };
+            store.labels.push(label.clone());
+            Ok(label)
+        })
+    }
+
+    fn set_task_labels(
+        &self,
+        task_id: i64,
+        label_ids: Vec<i64>,
+    ) -> impl Future<Output = Result<(), Error>> + Send + use<> {
+        self.with_store(|store| {
+            let labels: Vec<Label> = store
+                .labels
+                .iter()
+                .filter(|label| label_ids.contains(&label.id))
+                .cloned()
+                .collect();
+            if let Some(task) = store.task_mut(task_id) {
+                task.labels = labels;
+            }
+            Ok(())
+        })
+    }
+
+    fn set_task_dependencies(
+        &self,
+        task_id: i64,
+        blocked_by: Vec<i64>,
+    ) -> impl Future<Output = Result<(), Error>> + Send + use<> {
+        self.with_store(|store| {
+            if let Some(task) = store.task_mut(task_id) {
+                task.blocked_by = blocked_by;
+            }
+            Ok(())
+        })
+    }
+
+    fn insert_subtask(
+        &self,
+        task_id: i64,
+        title: String,
+    ) -> impl Future<Output = Result<Subtask, Error>> + Send + use<> {
+        self.with_store(|store| {
+            store.last_subtask_id += 1;
+            let id = store.last_subtask_id;
+            let task = store
+                .task_mut(task_id)
+                .ok_or_else(|| Error::Invalid(format!("No task #{task_id}")))?;
+            let subtask = Subtask {
+                id,
+                task_id,
+                title,
+                done: false,
+                position: task
+                    .subtasks
+                    .iter()
+                    .map(|s| s.position + 1)
+                    .max()
+                    .unwrap_or_default(),
+            };
+            task.subtasks.push(subtask.clone());
+            Ok(subtask)
+        })
+    }
+
+    fn set_subtask_done(
+        &self,
+        subtask_id: i64,
+        done: bool,
+    ) -> impl Future<Output = Result<(), Error>> + Send + use<> {
+        self.with_store(|store| {
+            let subtask = store
+                .tasks
+                .iter_mut()
+                .flat_map(|(_, task)| &mut task.subtasks)
+                .find(|subtask| subtask.id == subtask_id);
+            if let Some(subtask) = subtask {
+                subtask.done = done;
+            }
+            Ok(())
+        })
+    }
+
+    fn remove_subtask(
+        &self,
+        subtask_id: i64,
+    ) -> impl Future<Output = Result<(), Error>> + Send + use<> {
+        self.with_store(|store| {
+            for (_, task) in &mut store.tasks {
+                task.subtasks.retain(|subtask| subtask.id != subtask_id);
+            }
+            Ok(())
+        })
+    }
+
+    fn get_task_events(
+        &self,
+        _task_id: i64,
+    ) -> impl Future<Output = Result<Vec<TaskEvent>, Error>> + Send + use<> {
+        std::future::ready(Ok(vec![]))
+    }
+
+    fn get_board_events(
+        &self,
+        _board_id: i64,
+    ) -> impl Future<Output = Result<Vec<TaskEvent>, Error>> + Send + use<> {
+        std::future::ready(Ok(vec![]))
+    }
+}
+
+async fn fetch_tasks(
+    pool: Pool<Sqlite>,
+    board_id: i64,
+    archived: bool,
+) -> Result<Vec<Task>, Error> {
+    let rows = sqlx::query!(
+        r#"SELECT id, title, description, lane, position, due_date as "due_date: NaiveDate",
+            priority as "priority: Priority", archived_at as "archived_at: DateTime<Utc>",
+            created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+        FROM tasks WHERE board_id = ? AND (archived_at IS NOT NULL) = ?
+        ORDER BY archived_at DESC, position, id"#,
+        board_id,
+        archived
+    )
+    .fetch_all(&pool)
+    .map_err(Error::db("loading tasks"))
+    .await?;
+
+    // Labels for the whole board are fetched in one query and grouped by task
+    let label_rows = sqlx::query!(
+        "SELECT task_labels.task_id, labels.id, labels.name, labels.color
+        FROM task_labels
+        JOIN labels ON labels.id = task_labels.label_id
+        JOIN tasks ON tasks.id = task_labels.task_id
+        WHERE tasks.board_id = ? AND (tasks.archived_at IS NOT NULL) = ?
+        ORDER BY labels.name",
+        board_id,
+        archived
+    )
+    .fetch_all(&pool)
+    .map_err(Error::db("loading task labels"))
+    .await?;
+
+    let subtask_rows = sqlx::query_as!(
+        Subtask,
+        r#"SELECT subtasks.id as "id!", subtasks.task_id, subtasks.title, subtasks.done,
+            subtasks.position
+        FROM subtasks
+        JOIN tasks ON tasks.id = subtasks.task_id
+        WHERE tasks.board_id = ? AND (tasks.archived_at IS NOT NULL) = ?
+        ORDER BY subtasks.position, subtasks.id"#,
+        board_id,
+        archived
+    )
+    .fetch_all(&pool)
+    .map_err(Error::db("loading subtasks"))
+    .await?;
+
+    let dependency_rows = sqlx::query!(
+        "SELECT task_dependencies.task_id, task_dependencies.blocked_by_id
+        FROM task_dependencies
+        JOIN tasks ON tasks.id = task_dependencies.task_id
+        WHERE tasks.board_id = ? AND (tasks.archived_at IS NOT NULL) = ?",
+        board_id,
+        archived
+    )
+    .fetch_all(&pool)
+    .map_err(Error::db("loading task dependencies"))
+    .await?;
+
+    let mut labels_by_task: HashMap<i64, Vec<Label>> = HashMap::new();
+    for row in label_rows {
+        labels_by_task.entry(row.task_id).or_default().push(Label {
+            id: row.id,
+            name: row.name,
+            color: row.color,
+        });
+    }
+    let mut blockers_by_task: HashMap<i64, Vec<i64>> = HashMap::new();
+    for row in dependency_rows {
+        blockers_by_task
+            .entry(row.task_id)
+            .or_default()
+            .push(row.blocked_by_id);
+    }
+    let mut subtasks_by_task: HashMap<i64, Vec<Subtask>> = HashMap::new();
+    for subtask in subtask_rows {
+        subtasks_by_task
+            .entry(subtask.task_id)
+            .or_default()
+            .push(subtask);
+    }
+
+    Ok(rows
+        .into_iter()
+        .map(|row| Task {
+            labels: labels_by_task.remove(&row.id).unwrap_or_default(),
+            subtasks: subtasks_by_task.remove(&row.id).unwrap_or_default(),
+            blocked_by: blockers_by_task.remove(&row.id).unwrap_or_default(),
+            id: row.id,
+            title: row.title,
+            description: row.description,
+            lane: row.lane,
+            position: row.position,
+            due_date: row.due_date,
+            priority: row.priority,
+            archived_at: row.archived_at,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        })
+        .collect())
+}
+
+async fn insert_task(pool: Pool<Sqlite>, t: NewTask) -> Result<Task, Error> {
+    let mut tx = pool
+        .begin()
+        .map_err(Error::db("starting transaction"))
+        .await?;
+    let inserted = sqlx::query!(
+        r#"INSERT INTO tasks (board_id, title, description, lane, due_date, priority, position,
+            created_at, updated_at)
+        VALUES (?, ?, ?, ?, ?, ?, (SELECT COALESCE(MAX(position) + 1, 0) FROM tasks
+            WHERE board_id = ? AND lane = ?),
+            strftime('%Y-%m-%d %H:%M:%f', 'now'), strftime('%Y-%m-%d %H:%M:%f', 'now'))
+        RETURNING id, position, created_at as "created_at!: DateTime<Utc>""#,
+        t.board_id,
+        t.title,
+        t.description,
+        t.lane,
+        t.due_date,
+        t.priority,
+        t.board_id,
+        t.lane
+    )
+    .fetch_one(&mut *tx)
+    .map_err(Error::db("inserting task into db"))
+    .await?;
+    for label in &t.labels {
+        sqlx::query!(
+            "INSERT INTO task_labels (task_id, label_id) VALUES (?, ?)",
+            inserted.id,
+            label.id
+        )
+        .execute(&mut *tx)
+        .map_err(Error::db("adding task label"))
+        .await?;
+    }
+    for blocked_by_id in &t.blocked_by {
+        sqlx::query!(
+            "INSERT INTO task_dependencies (task_id, blocked_by_id) VALUES (?, ?)",
+            inserted.id,
+            blocked_by_id
+        )
+        .execute(&mut *tx)
+        .map_err(Error::db("adding task dependency"))
+        .await?;
+    }
+    tx.commit()
+        .map_err(Error::db("committing new task"))
+        .await?;
+
+    Ok(Task {
+        id: inserted.id,
+        title: t.title,
+        description: t.description,
+        lane: t.lane,
+        position: inserted.position,
+        due_date: t.due_date,
+        priority: t.priority,
+        labels: t.labels,
+        subtasks: vec![],
+        blocked_by: t.blocked_by,
+        archived_at: None,
+        created_at: inserted.created_at,
+        updated_at: inserted.created_at,
+    })
+}
+
+async fn archive_task(
+    pool: Pool<Sqlite>,
+    task_id: i64,
+    archived_at: Option<DateTime<Utc>>,
+) -> Result<(), Error> {
+    sqlx::query!(
+        "UPDATE tasks SET archived_at = ? WHERE id = ?",
+        archived_at,
+        task_id
+    )
+    .execute(&pool)
+    .map_err(Error::db("archiving task in db"))
+    .map_ok(|_| ())
+    .await
+}
+
+async fn remove_task(pool: Pool<Sqlite>, task_id: i64) -> Result<(), Error> {
+    sqlx::query!("DELETE FROM tasks WHERE id = ?", task_id)
+        .execute(&pool)
+        .map_err(Error::db("deleting task from db"))
+        .map_ok(|_| ())
+        .await
+}
+
+async fn edit_task(pool: Pool<Sqlite>, task: Task) -> Result<(), Error> {
+    sqlx::query!(
+        "UPDATE tasks SET title = ?, description = ?, lane = ?, position = ?, due_date = ?,
+            priority = ?
+        WHERE id = ?",
+        task.title,
+        task.description,
+        task.lane,
+        task.position,
+        task.due_date,
+        task.priority,
+        task.id
+    )
+    .execute(&pool)
+    .map_err(Error::db("updating task in db"))
+    .map_ok(|_| ())
+    .await
+}
+
+// Quotes each search term so user input can't break the FTS5 query syntax,
+// and makes every term a prefix match.
+fn fts_query(query: &str) -> String {
+    query
+        .split_whitespace()
+        .map(|term| format!("\"{}\"*", term.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+async fn search_tasks(
+    pool: Pool<Sqlite>,
+    board_id: i64,
+    query: String,
+) -> Result<Vec<SearchMatch>, Error> {
+    let fts_query = fts_query(&query);
+    if fts_query.is_empty() {
+        return Ok(vec![]);
+    }
+    sqlx::query_as!(
+        SearchMatch,
+        r#"SELECT tasks.id as "task_id!",
+            highlight(tasks_fts, 0, char(2), char(3)) as "title!: String",
+            COALESCE(snippet(tasks_fts, 1, char(2), char(3), '...', 16), '') as "snippet!: String"
+        FROM tasks_fts
+        JOIN tasks ON tasks.id = tasks_fts.rowid
+        WHERE tasks_fts MATCH ? AND tasks.board_id = ? AND tasks.archived_at IS NULL
+        ORDER BY rank"#,
+        fts_query,
+        board_id
+    )
+    .fetch_all(&pool)
+    .map_err(Error::db("searching tasks"))
+    .await
+}
+
+// Writes the `to` state of an undo/redo transition: tasks only in `from` are
+// deleted, and every task in `to` is restored or overwritten with its recorded
+// fields and labels.
+async fn apply_change(
+    pool: Pool<Sqlite>,
+    board_id: i64,
+    from: Vec<Task>,
+    to: Vec<Task>,
+) -> Result<(), Error> {
+    let mut tx = pool
+        .begin()
+        .map_err(Error::db("starting transaction"))
+        .await?;
+    for task in from
+        .iter()
+        .filter(|t| !to.iter().any(|other| other.id == t.id))
+    {
+        sqlx::query!("DELETE FROM tasks WHERE id = ?", task.id)
+            .execute(&mut *tx)
+            .map_err(Error::db("deleting task from db"))
+            .await?;
+    }
+    for task in &to {
+        sqlx::query!(
+            "INSERT INTO tasks (id, board_id, title, description, lane, position, due_date, priority,
+                archived_at, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(id) DO UPDATE SET title = excluded.title,
+                description = excluded.description, lane = excluded.lane,
+                position = excluded.position, due_date = excluded.due_date,
+                priority = excluded.priority, archived_at = excluded.archived_at",
+            task.id,
+            board_id,
+            task.title,
+            task.description,
+            task.lane,
+            task.position,
+            task.due_date,
+            task.priority,
+            task.archived_at,
+            task.created_at
+        )
+        .execute(&mut *tx)
+        .map_err(Error::db("restoring task in db"))
+        .await?;
+        sqlx::query!("DELETE FROM task_labels WHERE task_id = ?", task.id)
+            .execute(&mut *tx)
+            .map_err(Error::db("clearing task labels"))
+            .await?;
+        for label in &task.labels {
+            sqlx::query!(
+                "INSERT OR IGNORE INTO task_labels (task_id, label_id)
+                SELECT ?, id FROM labels WHERE id = ?",
+                task.id,
+                label.id
+            )
+            .execute(&mut *tx)
+            .map_err(Error::db("adding task label"))
+            .await?;
+        }
+        sqlx::query!("DELETE FROM task_dependencies WHERE task_id = ?", task.id)
+            .execute(&mut *tx)
+            .map_err(Error::db("clearing task dependencies"))
+            .await?;
+        for blocked_by_id in &task.blocked_by {
+            sqlx::query!(
+                "INSERT OR IGNORE INTO task_dependencies (task_id, blocked_by_id)
+                SELECT ?, id FROM tasks WHERE id = ?",
+                task.id,
+                blocked_by_id
+            )
+            .execute(&mut *tx)
+            .map_err(Error::db("adding task dependency"))
+            .await?;
+        }
+        // Only restores checklist items lost when the task itself was deleted
+        for subtask in &task.subtasks {
+            sqlx::query!(
+                "INSERT OR IGNORE INTO subtasks (id, task_id, title, done, position)
+                VALUES (?, ?, ?, ?, ?)",
+                subtask.id,
+                subtask.task_id,
+                subtask.title,
+                subtask.done,
+                subtask.position
+            )
+            .execute(&mut *tx)
+            .map_err(Error::db("restoring subtask"))
+            .await?;
+        }
+    }
+    tx.commit().map_err(Error::db("committing undo")).await
+}
+
+async fn archive_stale_tasks(
+    pool: Pool<Sqlite>,
+    board_id: i64,
+    lane: String,
+    days: u32,
+) -> Result<u64, Error> {
+    let archived_at = Utc::now();
+    sqlx::query!(
+        "UPDATE tasks SET archived_at = ?
+        WHERE board_id = ? AND lane = ? AND archived_at IS NULL
+            AND julianday('now') - julianday(lane_entered_at) >= ?",
+        archived_at,
+        board_id,
+        lane,
+        days
+    )
+    .execute(&pool)
+    .map_err(Error::db("archiving stale tasks in db"))
+    .map_ok(|res| res.rows_affected())
+    .await
+}
+
+async fn update_task_positions(pool: Pool<Sqlite>, tasks: Vec<Task>) -> Result<(), Error> {
+    let mut tx = pool
+        .begin()
+        .map_err(Error::db("starting transaction"))
+        .await?;
+    for task in tasks {
+        sqlx::query!(
+            "UPDATE tasks SET lane = ?, position = ? WHERE id = ?",
+            task.lane,
+            task.position,
+            task.id
+        )
+        .execute(&mut *tx)
+        .map_err(Error::db("updating task position in db"))
+        .await?;
+    }
+    tx.commit()
+        .map_err(Error::db("committing task positions"))
+        .await
+}
+
+// A renamed lane keeps its history: its tasks aren't recorded as moving, keep
+// the time they entered the lane, and past events take the new name. Tasks in
+// removed lanes are really moved, so they get a single move event each.
+async fn move_lanes(
+    pool: Pool<Sqlite>,
+    board_id: i64,
+    renames: HashMap<String, String>,
+    moves: HashMap<String, String>,
+) -> Result<(), Error> {
+    let mut tx = pool
+        .begin()
+        .map_err(Error::db("starting transaction"))
+        .await?;
+
+    // Go through placeholder names first so swapped lane names don't collide,
+    // and move tasks out of removed lanes before another lane takes their name
+    let renames: Vec<(String, String, String)> = renames
+        .into_iter()
+        .enumerate()
+        .map(|(idx, (from, to))| (from, format!("\u{0}lane-rename-{idx}"), to))
+        .collect();
+    set_renaming(&mut tx, board_id, true).await?;
+    for (from, placeholder, _) in &renames {
+        rename_lane(&mut tx, board_id, from, placeholder).await?;
+    }
+    set_renaming(&mut tx, board_id, false).await?;
+    for (from, to) in &moves {
+        set_lane(&mut tx, board_id, from, to).await?;
+    }
+    set_renaming(&mut tx, board_id, true).await?;
+    for (_, placeholder, to) in &renames {
+        rename_lane(&mut tx, board_id, placeholder, to).await?;
+    }
+    set_renaming(&mut tx, board_id, false).await?;
+
+    tx.commit()
+        .map_err(Error::db("committing lane changes"))
+        .await
+}
+
+// The lane triggers skip boards listed in lane_renames
+async fn set_renaming(
+    tx: &mut Transaction<'_, Sqlite>,
+    board_id: i64,
+    renaming: bool,
+) -> Result<(), Error> {
+    let query = if renaming {
+        sqlx::query!("INSERT INTO lane_renames (board_id) VALUES (?)", board_id)
+    } else {
+        sqlx::query!("DELETE FROM lane_renames WHERE board_id = ?", board_id)
+    };
+    query
+        .execute(&mut **tx)
+        .map_err(Error::db("recording lane renames"))
+        .map_ok(|_| ())
+        .await
+}
+
+async fn set_lane(
+    tx: &mut Transaction<'_, Sqlite>,
+    board_id: i64,
+    from: &str,
+    to: &str,
+) -> Result<(), Error> {
+    sqlx::query!(
+        "UPDATE tasks SET lane = ? WHERE board_id = ? AND lane = ?",
+        to,
+        board_id,
+        from
+    )
+    .execute(&mut **tx)
+    .map_err(Error::db("moving tasks between lanes"))
+    .map_ok(|_| ())
+    .await
+}
+
+// Renames a lane on the board's tasks and throughout their history
+async fn rename_lane(
+    tx: &mut Transaction<'_, Sqlite>,
+    board_id: i64,
+    from: &str,
+    to: &str,
+) -> Result<(), Error> {
+    set_lane(tx, board_id, from, to).await?;
+    sqlx::query!(
+        "UPDATE task_events SET from_lane = ?
+        WHERE from_lane = ? AND task_id IN (SELECT id FROM tasks WHERE board_id = ?)",
+        to,
+        from,
+        board_id
+    )
+    .execute(&mut **tx)
+    .map_err(Error::db("renaming lane in task history"))
+    .await?;
+    sqlx::query!(
+        "UPDATE task_events SET to_lane = ?
+        WHERE to_lane = ? AND task_id IN (SELECT id FROM tasks WHERE board_id = ?)",
+        to,
+        from,
+        board_id
+    )
+    .execute(&mut **tx)
+    .map_err(Error::db("renaming lane in task history"))
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use super::*;
+
+    fn new_task(title: &str, lane: &str) -> NewTask {
+        NewTask {
+            board_id: 1,
+            title: title.into(),
+            lane: lane.into(),
+            ..NewTask::default()
+        }
+    }
+
+    // Every connection to sqlite::memory: opens its own database, so keep just one
+    async fn sqlite_repo() -> SqliteTaskRepository {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .idle_timeout(None)
+            .max_lifetime(None)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!().run(&pool).await.unwrap();
+        SqliteTaskRepository::new(pool)
+    }
+
+    async fn backdate_lane_times(repo: &SqliteTaskRepository, days: u32) {
+        sqlx::query("UPDATE tasks SET lane_entered_at = strftime('%Y-%m-%d %H:%M:%f', 'now', ?)")
+            .bind(format!("-{days} days"))
+            .execute(&repo.pool)
+            .await
+            .unwrap();
+    }
+
+    async fn history(repo: &impl TaskRepository, task_id: i64) -> Vec<String> {
+        repo.get_task_events(task_id)
+            .await
+            .unwrap()
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn inserted_tasks_are_listed_in_lane_order() {
+        let repo = InMemoryTaskRepository::new();
+        let first = repo.insert_task(new_task("First", "Todo")).await.unwrap();
+        let second = repo.insert_task(new_task("Second", "Todo")).await.unwrap();
+        let other = repo.insert_task(new_task("Other", "Done")).await.unwrap();
+
+        assert_eq!((first.position, second.position, other.position), (0, 1, 0));
+        let tasks = repo.get_tasks(1).await.unwrap();
+        let ids: Vec<i64> = tasks.iter().map(|task| task.id).collect();
+        assert_eq!(ids, vec![first.id, other.id, second.id]);
+        assert!(repo.get_tasks(2).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn edits_keep_labels_and_creation_time() {
+        let repo = InMemoryTaskRepository::new();
+        let label = repo.insert_label("Bug".into()).await.unwrap();
+        let task = repo.insert_task(new_task("Task", "Todo")).await.unwrap();
+        repo.set_task_labels(task.id, vec![label.id]).await.unwrap();
+
+        repo.edit_task(Task {
+            title: "Renamed".into(),
+            lane: "Done".into(),
+            labels: vec![],
+            ..task.clone()
+        })
+        .await
+        .unwrap();
+
+        let edited = &repo.get_tasks(1).await.unwrap()[0];
+        assert_eq!(edited.title, "Renamed");
+        assert_eq!(edited.lane, "Done");
+        assert_eq!(edited.labels, vec![label]);
+        assert_eq!(edited.created_at, task.created_at);
+    }
+
+    #[tokio::test]
+    async fn archived_tasks_move_off_the_board_until_restored() {
+        let repo = InMemoryTaskRepository::new();
+        let task = repo.insert_task(new_task("Task", "Todo")).await.unwrap();
+
+        repo.archive_task(task.id, Some(Utc::now())).await.unwrap();
+        assert!(repo.get_tasks(1).await.unwrap().is_empty());
+        assert_eq!(repo.get_archived_tasks(1).await.unwrap().len(), 1);
+
+        repo.archive_task(task.id, None).await.unwrap();
+        assert_eq!(repo.get_tasks(1).await.unwrap().len(), 1);
+        assert!(repo.get_archived_tasks(1).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn removed_tasks_no_longer_block_others() {
+        let repo = InMemoryTaskRepository::new();
+        let blocker = repo.insert_task(new_task("Blocker", "Todo")).await.unwrap();
+        let task = repo
+            .insert_task(NewTask {
+                blocked_by: vec![blocker.id],
+                ..new_task("Task", "Todo")
+            })
+            .await
+            .unwrap();
+
+        repo.remove_task(blocker.id).await.unwrap();
+        let tasks = repo.get_tasks(1).await.unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].id, task.id);
+        assert!(tasks[0].blocked_by.is_empty());
+    }
+
+    #[tokio::test]
+    async fn renamed_lanes_are_not_moves() {
+        let repo = InMemoryTaskRepository::new();
+        let renamed = repo.insert_task(new_task("Renamed", "Todo")).await.unwrap();
+        let moved = repo.insert_task(new_task("Moved", "Later")).await.unwrap();
+        repo.with_store(|store| {
+            store
+                .lane_entered_at
+                .insert(renamed.id, Utc::now() - chrono::Days::new(10));
+            store
+                .lane_entered_at
+                .insert(moved.id, Utc::now() - chrono::Days::new(10));
+        })
+        .await;
+
+        let renames = HashMap::from([("Todo".to_string(), "Backlog".to_string())]);
+        let moves = HashMap::from([("Later".to_string(), "Backlog".to_string())]);
+        repo.move_lanes(1, renames, moves).await.unwrap();
+
+        let archived = repo
+            .archive_stale_tasks(1, "Backlog".into(), 7)
+            .await
+            .unwrap();
+        assert_eq!(archived, 1);
+        let archived = repo.get_archived_tasks(1).await.unwrap();
+        assert_eq!(archived[0].id, renamed.id);
+        assert_eq!(repo.get_tasks(1).await.unwrap()[0].id, moved.id);
+    }
+
+    #[tokio::test]
+    async fn sqlite_lane_changes_rename_history_and_record_real_moves() {
+        let repo = sqlite_repo().await;
+        let task = repo.insert_task(new_task("Task", "Todo")).await.unwrap();
+        repo.edit_task(Task {
+            lane: "Doing".into(),
+            ..task.clone()
+        })
+        .await
+        .unwrap();
+        let later = repo
+            .insert_task(new_task("Later", "Someday"))
+            .await
+            .unwrap();
+        backdate_lane_times(&repo, 10).await;
+
+        let renames = HashMap::from([("Doing".to_string(), "Review".to_string())]);
+        let moves = HashMap::from([("Someday".to_string(), "Review".to_string())]);
+        repo.move_lanes(1, renames, moves).await.unwrap();
+
+        assert_eq!(
+            history(&repo, task.id).await,
+            ["Created in Todo", "Moved from Todo to Review"]
+        );
+        assert_eq!(
+            history(&repo, later.id).await,
+            ["Created in Someday", "Moved from Someday to Review"]
+        );
+        // Only the renamed task kept its time in the lane
+        let archived = repo
+            .archive_stale_tasks(1, "Review".into(), 7)
+            .await
+            .unwrap();
+        assert_eq!(archived, 1);
+        assert_eq!(repo.get_archived_tasks(1).await.unwrap()[0].id, task.id);
+    }
+
+    #[tokio::test]
+    async fn sqlite_changes_restore_overwrite_and_delete_tasks() {
+        let repo = sqlite_repo().await;
+        let label = repo.insert_label("Bug".into()).await.unwrap();
+        let task = repo.insert_task(new_task("Task", "Todo")).await.unwrap();
+        repo.set_task_labels(task.id, vec![label.id]).await.unwrap();
+        let task = repo.get_tasks(1).await.unwrap().remove(0);
+
+        repo.remove_task(task.id).await.unwrap();
+        repo.apply_change(1, vec![], vec![task.clone()])
+            .await
+            .unwrap();
+        let restored = repo.get_tasks(1).await.unwrap();
+        assert_eq!(restored.len(), 1);
+        assert_eq!(
+            (restored[0].id, restored[0].labels.clone()),
+            (task.id, vec![label])
+        );
+
+        let edited = Task {
+            title: "Edited".into(),
+            lane: "Done".into(),
+            ..task.clone()
+        };
+        repo.apply_change(1, vec![task], vec![edited.clone()])
+            .await
+            .unwrap();
+        let tasks = repo.get_tasks(1).await.unwrap();
+        assert_eq!(
+            (tasks[0].title.as_str(), tasks[0].lane.as_str()),
+            ("Edited", "Done")
+        );
+
+        repo.apply_change(1, vec![edited], vec![]).await.unwrap();
+        assert!(repo.get_tasks(1).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn sqlite_search_matches_word_prefixes_on_the_board() {
+        let repo = sqlite_repo().await;
+        let login = repo
+            .insert_task(NewTask {
+                description: Some("Users can't sign in".into()),
+                ..new_task("Fix login bug", "Todo")
+            })
+            .await
+            .unwrap();
+        repo.insert_task(new_task("Write docs", "Todo"))
+            .await
+            .unwrap();
+        let logout = repo
+            .insert_task(new_task("Fix logout", "Done"))
+            .await
+            .unwrap();
+        repo.archive_task(logout.id, Some(Utc::now()))
+            .await
+            .unwrap();
+
+        let matches = repo.search_tasks(1, "log".into()).await.unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].task_id, login.id);
+        assert_eq!(matches[0].title, "Fix \u{2}login\u{3} bug");
+
+        let matches = repo.search_tasks(1, "sign".into()).await.unwrap();
+        assert_eq!(matches[0].task_id, login.id);
+        assert!(matches[0].snippet.contains("\u{2}sign\u{3}"));
+
+        assert!(repo.search_tasks(2, "log".into()).await.unwrap().is_empty());
+        assert!(repo.search_tasks(1, "\"".into()).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn sqlite_only_stale_tasks_in_the_lane_are_archived() {
+        let repo = sqlite_repo().await;
+        let stale = repo.insert_task(new_task("Stale", "Done")).await.unwrap();
+        repo.insert_task(new_task("Waiting", "Todo")).await.unwrap();
+        backdate_lane_times(&repo, 10).await;
+        repo.insert_task(new_task("Fresh", "Done")).await.unwrap();
+
+        let archived = repo.archive_stale_tasks(1, "Done".into(), 7).await.unwrap();
+        assert_eq!(archived, 1);
+        let archived = repo.get_archived_tasks(1).await.unwrap();
+        assert_eq!(archived[0].id, stale.id);
+        assert_eq!(
+            history(&repo, stale.id).await,
+            ["Created in Done", "Archived"]
+        );
+        assert_eq!(repo.get_tasks(1).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn sqlite_swapped_lane_names_keep_their_tasks_and_times() {
+        let repo = sqlite_repo().await;
+        let todo = repo.insert_task(new_task("Todo", "Todo")).await.unwrap();
+        let done = repo.insert_task(new_task("Done", "Todo")).await.unwrap();
+        repo.edit_task(Task {
+            lane: "Done".into(),
+            ..done.clone()
+        })
+        .await
+        .unwrap();
+        let before = repo.get_tasks(1).await.unwrap();
+
+        let renames = HashMap::from([
+            ("Todo".to_string(), "Done".to_string()),
+            ("Done".to_string(), "Todo".to_string()),
+        ]);
+        repo.move_lanes(1, renames, HashMap::new()).await.unwrap();
+
+        let after = repo.get_tasks(1).await.unwrap();
+        let lanes: Vec<(i64, &str)> = after.iter().map(|t| (t.id, t.lane.as_str())).collect();
+        assert!(lanes.contains(&(todo.id, "Done")) && lanes.contains(&(done.id, "Todo")));
+        for task in &after {
+            let old = before.iter().find(|old| old.id == task.id).unwrap();
+            assert_eq!(task.updated_at, old.updated_at);
+        }
+        assert_eq!(history(&repo, todo.id).await, ["Created in Done"]);
+        assert_eq!(
+            history(&repo, done.id).await,
+            ["Created in Done", "Moved from Done to Todo"]
+        );
+    }
+
+    #[tokio::test]
+    async fn sqlite_removed_lane_tasks_move_into_a_renamed_lane() {
+        let repo = sqlite_repo().await;
+        let waiting = repo.insert_task(new_task("Waiting", "Todo")).await.unwrap();
+        let started = repo.insert_task(new_task("Started", "Todo")).await.unwrap();
+        repo.edit_task(Task {
+            lane: "Doing".into(),
+            ..started.clone()
+        })
+        .await
+        .unwrap();
+        let finished = repo
+            .insert_task(new_task("Finished", "Done"))
+            .await
+            .unwrap();
+        backdate_lane_times(&repo, 10).await;
+
+        // "Doing" is removed and "Done" takes over its name
+        let renames = HashMap::from([
+            ("Todo".to_string(), "Backlog".to_string()),
+            ("Done".to_string(), "Doing".to_string()),
+        ]);
+        let moves = HashMap::from([("Doing".to_string(), "Backlog".to_string())]);
+        repo.move_lanes(1, renames, moves).await.unwrap();
+
+        assert_eq!(history(&repo, waiting.id).await, ["Created in Backlog"]);
+        assert_eq!(
+            history(&repo, started.id).await,
+            [
+                "Created in Backlog",
+                "Moved from Backlog to Doing",
+                "Moved from Doing to Backlog"
+            ]
+        );
+        assert_eq!(history(&repo, finished.id).await, ["Created in Doing"]);
+        let stale = |lane: &str| repo.archive_stale_tasks(1, lane.into(), 7);
+        assert_eq!(
+            (
+                stale("Backlog").await.unwrap(),
+                stale("Doing").await.unwrap()
+            ),
+            (1, 1)
+        );
+        let mut archived: Vec<i64> = repo
+            .get_archived_tasks(1)
+            .await
+            .unwrap()
+            .iter()
+            .map(|task| task.id)
+            .collect();
+        archived.sort();
+        assert_eq!(archived, [waiting.id, finished.id]);
+        assert_eq!(repo.get_tasks(1).await.unwrap()[0].lane, "Backlog");
+    }
+}
@@ -0,0 +1,267 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Datelike, Days, Local, NaiveDate, TimeDelta, Utc};
+use iced::Element;
+use iced::futures::TryFutureExt;
+use iced::widget::{column, scrollable, text};
+
+use crate::cfd::CumulativeFlow;
+use crate::error::Error;
+use crate::event::{EventKind, TaskEvent};
+use crate::layout::bar_chart;
+use crate::repository::TaskRepository;
+
+const WEEKS_SHOWN: u64 = 8;
+
+#[derive(Clone, Debug, Default)]
+pub struct Statistics {
+    completed: usize,
+    lead_time: Option<TimeDelta>,
+    lane_times: Vec<(String, Option<TimeDelta>)>,
+    throughput: Vec<(NaiveDate, usize)>,
+    flow: CumulativeFlow,
+}
+
+impl Statistics {
+    // A task is finished once it sits in the final lane, and its lead time runs
+    // from creation to its last arrival there.
+    pub fn from_events(events: &[TaskEvent], lanes: &[String], today: NaiveDate) -> Self {
+        let final_lane = lanes.last();
+        let mut created: HashMap<i64, DateTime<Utc>> = HashMap::new();
+        let mut current: HashMap<i64, (String, DateTime<Utc>)> = HashMap::new();
+        let mut finished: HashMap<i64, DateTime<Utc>> = HashMap::new();
+        let mut stays: HashMap<String, Vec<TimeDelta>> = HashMap::new();
+
+        for event in events {
+            match event.kind {
+                EventKind::Created => {
+                    created.insert(event.task_id, event.occurred_at);
+                }
+                EventKind::Moved => {
+                    if let Some((lane, entered_at)) = current.get(&event.task_id) {
+                        stays
+                            .entry(lane.clone())
+                            .or_default()
+                            .push(event.occurred_at - *entered_at);
+                    }
+                }
+                _ => continue,
+            }
+            let Some(lane) = &event.to_lane else {
+                continue;
+            };
+            current.insert(event.task_id, (lane.clone(), event.occurred_at));
+            if Some(lane) == final_lane {
+                finished.insert(event.task_id, event.occurred_at);
+            } else {
+                finished.remove(&event.task_id);
+            }
+        }
+
+        let lead_times: Vec<TimeDelta> = finished
+            .iter()
+            .filter_map(|(id, done_at)| created.get(id).map(|created_at| *done_at - *created_at))
+            .collect();
+        let lane_times = lanes
+            .iter()
+            .map(|lane| (lane.clone(), stays.get(lane).and_then(|s| average(s))))
+            .collect();
+
+        let this_week = week_start(today);
+        let throughput = (0..WEEKS_SHOWN)
+            .rev()
+            .map(|weeks_ago| {
+                let week = this_week - Days::new(weeks_ago * 7);
+                let count = finished
+                    .values()
+                    .filter(|at| week_start(at.with_timezone(&Local).date_naive()) == week)
+                    .count();
+                (week, count)
+            })
+            .collect();
+
+        Self {
+            completed: finished.len(),
+            lead_time: average(&lead_times),
+            lane_times,
+            throughput,
+            flow: CumulativeFlow::from_events(events, lanes, today),
+        }
+    }
+
+    pub fn view<'a, Message: 'a>(&'a self) -> Element<'a, Message> {
+        let lead_time = self
+            .lead_time
+            .map(format_duration)
+            .unwrap_or_else(|| "-".into());
+        let lane_times = self
+            .lane_times
+            .iter()
+            .map(|(lane, time)| {
+                let secs = time.map(|t| t.num_seconds() as f64).unwrap_or_default();
+                let label = time.map(format_duration).unwrap_or_else(|| "-".into());
+                (lane.clone(), secs, label)
+            })
+            .collect();
+        let throughput = self
+            .throughput
+            .iter()
+            .map(|(week, count)| {
+                (
+                    week.format("Week of %b %d").to_string(),
+                    *count as f64,
+                    count.to_string(),
+                )
+            })
+            .collect();
+
+        scrollable(
+            column![
+                text(format!("{} tasks completed", self.completed)),
+                text(format!("Average lead time: {lead_time}")),
+                text("Average cycle time per lane").size(20),
+                bar_chart(lane_times),
+                text("Weekly throughput").size(20),
+                bar_chart(throughput),
+                text("Cumulative flow").size(20),
+                self.flow.view(),
+            ]
+            .spacing(8),
+        )
+        .into()
+    }
+}
+
+fn average(times: &[TimeDelta]) -> Option<TimeDelta> {
+    let total: TimeDelta = times.iter().sum();
+    (!times.is_empty()).then(|| total / times.len() as i32)
+}
+
+fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Days::new(date.weekday().num_days_from_monday() as u64)
+}
+
+fn format_duration(time: TimeDelta) -> String {
+    if time.num_days() >= 1 {
+        format!("{:.1} days", time.num_minutes() as f64 / (24.0 * 60.0))
+    } else if time.num_hours() >= 1 {
+        format!("{} hours", time.num_hours())
+    } else {
+        format!("{} minutes", time.num_minutes())
+    }
+}
+
+pub async fn load_statistics(
+    repo: impl TaskRepository,
+    board_id: i64,
+    lanes: Vec<String>,
+) -> Result<Statistics, Error> {
+    repo.get_board_events(board_id)
+        .map_ok(|events| Statistics::from_events(&events, &lanes, Local::now().date_naive()))
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    // Times are local so tasks finish on the same calendar day in any timezone
+    fn event(task_id: i64, kind: EventKind, lanes: (&str, &str), day: u32, hour: u32) -> TaskEvent {
+        let lane = |name: &str| (!name.is_empty()).then(|| name.to_string());
+        TaskEvent {
+            task_id,
+            kind,
+            from_lane: lane(lanes.0),
+            to_lane: lane(lanes.1),
+            detail: None,
+            occurred_at: Local
+                .with_ymd_and_hms(2025, 11, day, hour, 0, 0)
+                .unwrap()
+                .with_timezone(&Utc),
+        }
+    }
+
+    fn created(task_id: i64, day: u32, hour: u32) -> TaskEvent {
+        event(task_id, EventKind::Created, ("", "Todo"), day, hour)
+    }
+
+    fn moved(task_id: i64, from: &str, to: &str, day: u32, hour: u32) -> TaskEvent {
+        event(task_id, EventKind::Moved, (from, to), day, hour)
+    }
+
+    fn lanes() -> Vec<String> {
+        vec!["Todo".to_string(), "Doing".to_string(), "Done".to_string()]
+    }
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 11, day).unwrap()
+    }
+
+    #[test]
+    fn lead_and_lane_times_average_over_finished_moves() {
+        let events = [
+            created(1, 3, 9),
+            created(2, 3, 9),
+            created(3, 4, 9),
+            moved(1, "Todo", "Doing", 3, 12),
+            moved(2, "Todo", "Doing", 3, 18),
+            moved(1, "Doing", "Done", 4, 12),
+            moved(2, "Doing", "Done", 5, 18),
+        ];
+        let stats = Statistics::from_events(&events, &lanes(), date(5));
+
+        assert_eq!(stats.completed, 2);
+        assert_eq!(stats.lead_time, Some(TimeDelta::hours(42)));
+        assert_eq!(
+            stats.lane_times,
+            [
+                ("Todo".to_string(), Some(TimeDelta::hours(6))),
+                ("Doing".to_string(), Some(TimeDelta::hours(36))),
+                ("Done".to_string(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn tasks_moved_back_out_of_the_final_lane_are_not_finished() {
+        let mut events = vec![
+            created(1, 3, 9),
+            moved(1, "Todo", "Done", 3, 12),
+            moved(1, "Done", "Doing", 4, 12),
+        ];
+        let stats = Statistics::from_events(&events, &lanes(), date(4));
+        assert_eq!((stats.completed, stats.lead_time), (0, None));
+        assert_eq!(stats.lane_times[2].1, Some(TimeDelta::hours(24)));
+
+        // Lead time runs to the last arrival in the final lane
+        events.push(moved(1, "Doing", "Done", 5, 9));
+        let stats = Statistics::from_events(&events, &lanes(), date(5));
+        assert_eq!(stats.completed, 1);
+        assert_eq!(stats.lead_time, Some(TimeDelta::hours(48)));
+    }
+
+    #[test]
+    fn throughput_counts_tasks_in_the_week_they_finished() {
+        // 2025-11-09 is a Sunday and 2025-11-17 a Monday
+        let events = [
+            created(1, 3, 9),
+            created(2, 3, 9),
+            created(3, 3, 9),
+            created(4, 3, 9),
+            moved(1, "Todo", "Done", 9, 23),
+            moved(2, "Todo", "Done", 10, 0),
+            moved(3, "Todo", "Done", 16, 12),
+            moved(4, "Todo", "Done", 17, 12),
+        ];
+        let stats = Statistics::from_events(&events, &lanes(), date(17));
+
+        assert_eq!(stats.throughput.len(), WEEKS_SHOWN as usize);
+        assert_eq!(stats.throughput[0], (date(17) - Days::new(49), 0));
+        assert_eq!(
+            stats.throughput[5..],
+            [(date(3), 1), (date(10), 2), (date(17), 1)]
+        );
+    }
+}
@@ -0,0 +1,56 @@
+use iced::futures::TryFutureExt;
+use sqlx::{Pool, Sqlite};
+
+use crate::error::Error;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Subtask {
+    pub id: i64,
+    pub task_id: i64,
+    pub title: String,
+    pub done: bool,
+    pub position: i64,
+}
+
+pub async fn insert_subtask(
+    pool: Pool<Sqlite>,
+    task_id: i64,
+    title: String,
+) -> Result<Subtask, Error> {
+    sqlx::query_as!(
+        Subtask,
+        r#"INSERT INTO subtasks (task_id, title, position)
+        VALUES (?, ?, (SELECT COALESCE(MAX(position) + 1, 0) FROM subtasks WHERE task_id = ?))
+        RETURNING id as "id!", task_id, title, done, position"#,
+        task_id,
+        title,
+        task_id
+    )
+    .fetch_one(&pool)
+    .map_err(Error::db("inserting subtask into db"))
+    .await
+}
+
+pub async fn set_subtask_done(
+    pool: Pool<Sqlite>,
+    subtask_id: i64,
+    done: bool,
+) -> Result<(), Error> {
+    sqlx::query!(
+        "UPDATE subtasks SET done = ? WHERE id = ?",
+        done,
+        subtask_id
+    )
+    .execute(&pool)
+    .map_err(Error::db("updating subtask in db"))
+    .map_ok(|_| ())
+    .await
+}
+
+pub async fn remove_subtask(pool: Pool<Sqlite>, subtask_id: i64) -> Result<(), Error> {
+    sqlx::query!("DELETE FROM subtasks WHERE id = ?", subtask_id)
+        .execute(&pool)
+        .map_err(Error::db("deleting subtask from db"))
+        .map_ok(|_| ())
+        .await
+}
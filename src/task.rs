@@ -1,42 +1,111 @@
-use std::collections::HashMap;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
 use std::vec;
 
-use iced::Element;
+use chrono::{Local, NaiveDate, Utc};
 use iced::futures::TryFutureExt;
-use iced::widget::{row, text_editor};
-use sqlx::{Pool, Sqlite};
+use iced::widget::{
+    Column, button, column, markdown, pick_list, row, scrollable, text, text_editor, text_input,
+};
+use iced::{Element, Length};
 
-use crate::layout::{swim_lane, task_card, task_dialog, task_dialog_mut};
+use crate::error::Error;
+use crate::event::TaskEvent;
+use crate::filter::{DueFilter, Filter};
+use crate::history::{Change, History};
+use crate::label::Label;
+use crate::lane::Lane;
+use crate::layout::{
+    CardActions, TaskForm, archived_task_row, checklist_editor, date_picker, dependency_picker,
+    label_picker, markdown_text, search_result, swim_lane, task_card, task_dialog, task_dialog_mut,
+    warning_banner,
+};
+use crate::model::{NewTask, Priority, SearchMatch, Task, TaskRef, next_position};
+use crate::repository::TaskRepository;
+use crate::subtask::Subtask;
 
-#[derive(Clone, Debug, Default)]
-pub struct Task {
-    pub id: i64,
-    pub title: String,
-    pub description: Option<String>,
-    pub lane: String,
-}
-
-#[derive(Clone, Debug, Default)]
-struct NewTask {
-    title: String,
-    description: Option<String>,
-    lane: String,
-}
+const UNASSIGNED: &str = "Unassigned";
+const SEARCH_RESULTS_SHOWN: usize = 10;
 
 #[derive(Clone, Debug)]
 pub enum Message {
-    TasksLoaded(Result<Vec<Task>, String>),
+    TasksLoaded(Result<Vec<Task>, Error>),
+    LabelsLoaded(Result<Vec<Label>, Error>),
     CreateTask,
+    TaskCreated(Result<Task, Error>),
     EditTask(i64),
-    RemoveTask(i64),
+    ArchiveTask(i64),
+    LoadArchive,
+    ArchiveLoaded(Result<Vec<Task>, Error>),
+    RestoreTask(i64),
+    ConfirmPurge(i64),
+    CancelPurge,
+    PurgeTask(i64),
     MoveToLane(String, i64),
+    MoveUp(i64),
+    MoveDown(i64),
+    Reorder(String, usize, i64),
+    DragStart(i64),
+    DragEnter(String),
+    DragLeave(String),
+    CardReleased(i64),
+    Drop,
+    DragEnded,
     OpenModal(Modal),
     CloseModal,
     TaskTitleUpdated(String),
     TaskDescUpdated(text_editor::Action),
+    DescriptionPreviewToggled(bool),
+    LinkClicked(markdown::Url),
+    LinkOpened(Result<(), Error>),
+    TaskDueDatePicked(Option<NaiveDate>),
+    TaskPriorityPicked(Priority),
+    SearchTextUpdated(String),
+    SearchResults(String, Result<Vec<SearchMatch>, Error>),
+    EventsLoaded(i64, Result<Vec<TaskEvent>, Error>),
+    LabelFilterPicked(Label),
+    PriorityFilterPicked(Priority),
+    DueFilterPicked(DueFilter),
+    ClearFilters,
+    TaskLabelToggled(i64, bool),
+    BlockerPicked(TaskRef),
+    BlockerRemoved(i64),
+    SubtaskToggled(i64, i64, bool),
+    NewSubtaskTitleUpdated(String),
+    AddSubtask(i64),
+    SubtaskAdded(Result<Subtask, Error>),
+    RemoveSubtask(i64, i64),
+    NewLabelNameUpdated(String),
+    CreateLabel,
+    LabelCreated(Result<Label, Error>),
+    DueDateMonthChanged(NaiveDate),
+    DismissWarning,
+    Undo,
+    Redo,
+    Saved(Result<(), Error>),
     NoOp,
 }
 
+impl Message {
+    // Failures the app should report, whether loading or writing
+    pub fn error(&self) -> Option<&Error> {
+        match self {
+            Message::TasksLoaded(Err(err))
+            | Message::LabelsLoaded(Err(err))
+            | Message::TaskCreated(Err(err))
+            | Message::ArchiveLoaded(Err(err))
+            | Message::SearchResults(_, Err(err))
+            | Message::EventsLoaded(_, Err(err))
+            | Message::SubtaskAdded(Err(err))
+            | Message::LabelCreated(Err(err))
+            | Message::LinkOpened(Err(err))
+            | Message::Saved(Err(err)) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[allow(clippy::enum_variant_names)]
 #[derive(Clone, Debug)]
 pub enum Modal {
     NewTask,
@@ -44,40 +113,118 @@ pub enum Modal {
     EditTask(i64),
 }
 
-pub struct ViewController {
+pub struct ViewController<R> {
     modal: Option<Modal>,
-    db: Pool<Sqlite>,
-    lanes: Vec<String>,
+    repo: R,
+    board_id: i64,
+    lanes: Vec<Lane>,
     tasks: Vec<Task>,
+    archived: Vec<Task>,
+    // Archived task waiting for the user to confirm it should be deleted
+    purge_pending: Option<i64>,
+    labels: Vec<Label>,
+    dragging: Option<i64>,
+    drop_target: Option<String>,
     new_task_title: String,
     new_task_description: text_editor::Content,
+    // Rendered description for the view dialog, or the edit dialog's preview
+    markdown: Vec<markdown::Item>,
+    previewing: bool,
+    new_task_due_date: Option<NaiveDate>,
+    new_task_priority: Priority,
+    new_task_labels: Vec<i64>,
+    new_task_blocked_by: Vec<i64>,
+    new_label_name: String,
+    new_subtask_title: String,
+    due_date_month: NaiveDate,
+    sort_by_priority: bool,
+    filter: Filter,
+    search_results: Vec<SearchMatch>,
+    events: Vec<TaskEvent>,
+    warning: Option<String>,
+    history: History,
 }
 
-impl NewTask {
-    pub fn new(title: String, description: Option<String>, lane: String) -> Self {
-        NewTask {
-            title,
-            description,
-            lane,
-        }
-    }
-}
-
-impl ViewController {
-    pub fn new(db: Pool<Sqlite>, lanes: Vec<String>) -> Self {
+impl<R: TaskRepository> ViewController<R> {
+    pub fn new(repo: R, board_id: i64, lanes: Vec<Lane>) -> Self {
         Self {
             modal: None,
-            db,
+            repo,
+            board_id,
             lanes,
             tasks: vec![],
+            archived: vec![],
+            purge_pending: None,
+            labels: vec![],
+            dragging: None,
+            drop_target: None,
             new_task_title: Default::default(),
             new_task_description: Default::default(),
+            markdown: vec![],
+            previewing: false,
+            new_task_due_date: None,
+            new_task_priority: Priority::default(),
+            new_task_labels: vec![],
+            new_task_blocked_by: vec![],
+            new_label_name: String::new(),
+            new_subtask_title: String::new(),
+            due_date_month: Local::now().date_naive(),
+            sort_by_priority: false,
+            filter: Filter::default(),
+            search_results: vec![],
+            events: vec![],
+            warning: None,
+            history: History::default(),
+        }
+    }
+
+    pub fn load(&self) -> iced::Task<Message> {
+        iced::Task::batch([
+            iced::Task::perform(self.repo.get_tasks(self.board_id), Message::TasksLoaded),
+            iced::Task::perform(self.repo.get_labels(), Message::LabelsLoaded),
+        ])
+    }
+
+    fn search(&self) -> iced::Task<Message> {
+        let query = self.filter.text.clone();
+        if query.trim().is_empty() {
+            return iced::Task::none();
         }
+        iced::Task::perform(
+            self.repo.search_tasks(self.board_id, query.clone()),
+            move |res| Message::SearchResults(query.clone(), res),
+        )
+    }
+
+    pub fn board_id(&self) -> i64 {
+        self.board_id
+    }
+
+    pub fn lanes(&self) -> &[Lane] {
+        &self.lanes
+    }
+
+    pub fn set_lanes(&mut self, lanes: Vec<Lane>) {
+        self.lanes = lanes;
+    }
+
+    pub fn set_sort_by_priority(&mut self, sort_by_priority: bool) {
+        self.sort_by_priority = sort_by_priority;
     }
 
     fn hide_dialog(&mut self) {
         self.new_task_title.clear();
         self.new_task_description = text_editor::Content::new();
+        self.markdown.clear();
+        self.previewing = false;
+        self.new_task_due_date = None;
+        self.new_task_priority = Priority::default();
+        self.new_task_labels.clear();
+        self.new_task_blocked_by.clear();
+        self.new_label_name.clear();
+        self.new_subtask_title.clear();
+        self.due_date_month = Local::now().date_naive();
+        self.warning = None;
         self.modal = None;
     }
 
@@ -89,66 +236,475 @@ impl ViewController {
         self.tasks.iter_mut().find(|task| task.id == id)
     }
 
+    pub fn is_modal_open(&self) -> bool {
+        self.modal.is_some()
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.history.can_undo()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.history.can_redo()
+    }
+
+    fn dependents(&self, task_id: i64) -> Vec<&Task> {
+        self.tasks
+            .iter()
+            .filter(|task| task.blocked_by.contains(&task_id))
+            .collect()
+    }
+
+    // Tasks that could block the task being edited without creating a cycle
+    fn blocker_options(&self) -> Vec<TaskRef> {
+        let mut excluded = HashSet::new();
+        if let Some(Modal::EditTask(task_id)) = self.modal {
+            let mut pending = vec![task_id];
+            while let Some(id) = pending.pop() {
+                if excluded.insert(id) {
+                    pending.extend(self.dependents(id).iter().map(|task| task.id));
+                }
+            }
+        }
+        self.tasks
+            .iter()
+            .filter(|task| !excluded.contains(&task.id))
+            .filter(|task| !self.new_task_blocked_by.contains(&task.id))
+            .map(TaskRef::from)
+            .collect()
+    }
+
+    fn selected_labels(&self) -> Vec<Label> {
+        self.labels
+            .iter()
+            .filter(|label| self.new_task_labels.contains(&label.id))
+            .cloned()
+            .collect()
+    }
+
+    // Moves the board from one recorded state of some tasks to another,
+    // used to both undo and redo changes.
+    fn transition(&mut self, from: Vec<Task>, to: Vec<Task>) -> iced::Task<Message> {
+        // Checklists aren't part of the history, so keep their current state
+        let to: Vec<Task> = to
+            .into_iter()
+            .map(|task| {
+                let current = self
+                    .tasks
+                    .iter()
+                    .chain(&self.archived)
+                    .find(|t| t.id == task.id);
+                match current {
+                    Some(current) => Task {
+                        subtasks: current.subtasks.clone(),
+                        ..task
+                    },
+                    None => task,
+                }
+            })
+            .collect();
+        self.tasks.retain(|task| {
+            !from.iter().any(|t| t.id == task.id) || to.iter().any(|t| t.id == task.id)
+        });
+        for task in &to {
+            match self.find_task_by_id_mut(task.id) {
+                Some(existing) => *existing = task.clone(),
+                None => self.tasks.push(task.clone()),
+            }
+        }
+        // Archived tasks live on the archive screen rather than the board
+        self.archived
+            .retain(|task| !to.iter().any(|t| t.id == task.id));
+        self.archived.extend(
+            self.tasks
+                .iter()
+                .filter(|t| t.archived_at.is_some())
+                .cloned(),
+        );
+        self.tasks.retain(|task| task.archived_at.is_none());
+        self.tasks.sort_by_key(|task| task.position);
+
+        iced::Task::perform(
+            self.repo.apply_change(self.board_id, from, to),
+            Message::Saved,
+        )
+        .chain(self.search())
+    }
+
+    fn lane_task_ids(&self, lane: &str) -> Vec<i64> {
+        self.tasks
+            .iter()
+            .filter(|task| task.lane == lane)
+            .map(|task| task.id)
+            .collect()
+    }
+
+    fn wip_warning(&self, lane: &str) -> Option<String> {
+        self.lanes
+            .iter()
+            .find(|l| l.name == lane)?
+            .wip_warning(self.lane_task_ids(lane).len())
+    }
+
+    // Moving a blocked task forward is allowed, so this only warns
+    fn warn_if_blocked(&mut self, task_id: i64, lane: &str) {
+        if let Some(warning) = self
+            .find_task_by_id(task_id)
+            .and_then(|task| task.blocked_warning(lane, &self.tasks, &self.lanes))
+        {
+            self.warning = Some(warning);
+        }
+    }
+
+    /// Places a task at `index` within `lane`, renumbering the lane's positions
+    /// and persisting every task whose lane or position changed.
+    fn place_task(&mut self, task_id: i64, lane: String, index: usize) -> iced::Task<Message> {
+        if self
+            .find_task_by_id(task_id)
+            .is_some_and(|task| task.lane != lane)
+            && let Some(warning) = self.wip_warning(&lane)
+        {
+            self.warning = Some(warning);
+            return iced::Task::none();
+        }
+        self.warn_if_blocked(task_id, &lane);
+        let mut ids: Vec<i64> = self
+            .lane_task_ids(&lane)
+            .into_iter()
+            .filter(|id| *id != task_id)
+            .collect();
+        ids.insert(index.min(ids.len()), task_id);
+
+        let mut before = vec![];
+        let mut changed = vec![];
+        for (position, id) in ids.into_iter().enumerate() {
+            if let Some(task) = self.find_task_by_id_mut(id) {
+                let position = position as i64;
+                if task.lane != lane || task.position != position {
+                    before.push(task.clone());
+                    task.lane = lane.clone();
+                    task.position = position;
+                    changed.push(task.clone());
+                }
+            }
+        }
+        self.tasks.sort_by_key(|task| task.position);
+
+        if changed.is_empty() {
+            iced::Task::none()
+        } else {
+            self.history.record(Change {
+                before,
+                after: changed.clone(),
+            });
+            iced::Task::perform(self.repo.update_task_positions(changed), Message::Saved)
+        }
+    }
+
+    fn drop_dragged_task(&mut self, before: Option<i64>) -> iced::Task<Message> {
+        let dragging = self.dragging.take();
+        let drop_target = self.drop_target.take();
+        match (dragging, drop_target) {
+            (Some(task_id), Some(lane)) => match before {
+                Some(before_id) => {
+                    let index = self
+                        .lane_task_ids(&lane)
+                        .into_iter()
+                        .filter(|id| *id != task_id)
+                        .position(|id| id == before_id)
+                        .unwrap_or(usize::MAX);
+                    iced::Task::done(Message::Reorder(lane, index, task_id))
+                }
+                None => iced::Task::done(Message::MoveToLane(lane, task_id)),
+            },
+            _ => iced::Task::none(),
+        }
+    }
+
     pub fn update(&mut self, msg: Message) -> iced::Task<Message> {
         match msg {
             Message::TasksLoaded(tasks) => {
                 if let Ok(tasks) = tasks {
-                    self.tasks = tasks
+                    self.tasks = tasks;
+                    self.tasks.sort_by_key(|task| task.position);
+                }
+                self.search()
+            }
+            Message::LabelsLoaded(labels) => {
+                if let Ok(labels) = labels {
+                    self.labels = labels;
                 }
                 iced::Task::none()
             }
             Message::CreateTask => {
                 let title = self.new_task_title.clone();
                 let desc = Some(self.new_task_description.text());
-                if let Some(lane) = self.lanes.get(0) {
-                    let task = NewTask::new(title, desc, lane.clone());
-                    iced::Task::perform(insert_task(self.db.clone(), task), |_| Message::CloseModal)
-                        .chain(iced::Task::perform(
-                            get_tasks(self.db.clone()),
-                            Message::TasksLoaded,
-                        ))
+                if let Some(lane) = self.lanes.first() {
+                    if let Some(warning) = self.wip_warning(&lane.name) {
+                        self.warning = Some(warning);
+                        return iced::Task::none();
+                    }
+                    let task = NewTask {
+                        blocked_by: self.new_task_blocked_by.clone(),
+                        ..NewTask::new(
+                            self.board_id,
+                            title,
+                            desc,
+                            lane.name.clone(),
+                            self.new_task_due_date,
+                            self.new_task_priority,
+                            self.selected_labels(),
+                        )
+                    };
+                    iced::Task::perform(self.repo.insert_task(task), Message::TaskCreated)
                 } else {
                     iced::Task::none()
                 }
             }
+            Message::TaskCreated(task) => match task {
+                Ok(task) => {
+                    self.history.record(Change {
+                        before: vec![],
+                        after: vec![task],
+                    });
+                    iced::Task::done(Message::CloseModal).chain(iced::Task::perform(
+                        self.repo.get_tasks(self.board_id),
+                        Message::TasksLoaded,
+                    ))
+                }
+                // The dialog stays open so the task can be saved again
+                Err(_) => iced::Task::none(),
+            },
             Message::EditTask(task_id) => {
                 let title = self.new_task_title.clone();
                 let desc = self.new_task_description.text();
-                let db = self.db.clone();
+                let due_date = self.new_task_due_date;
+                let priority = self.new_task_priority;
+                let label_ids = self.new_task_labels.clone();
+                let labels = self.selected_labels();
+                let blocked_by = self.new_task_blocked_by.clone();
+                let repo = self.repo.clone();
                 if let Some(task) = self.find_task_by_id_mut(task_id) {
+                    let before = task.clone();
                     task.title = title;
                     task.description = Some(desc);
-                    iced::Task::perform(edit_task(db, task.clone()), |_| Message::CloseModal)
+                    task.due_date = due_date;
+                    task.priority = priority;
+                    task.labels = labels;
+                    task.blocked_by = blocked_by.clone();
+                    task.updated_at = Utc::now();
+                    let after = task.clone();
+                    self.history.record(Change {
+                        before: vec![before],
+                        after: vec![after.clone()],
+                    });
+                    let labels_repo = repo.clone();
+                    let dependencies_repo = repo.clone();
+                    iced::Task::perform(
+                        repo.edit_task(after)
+                            .and_then(move |_| labels_repo.set_task_labels(task_id, label_ids))
+                            .and_then(move |_| {
+                                dependencies_repo.set_task_dependencies(task_id, blocked_by)
+                            }),
+                        Message::Saved,
+                    )
+                    .chain(self.search())
+                    .chain(iced::Task::done(Message::CloseModal))
                 } else {
                     iced::Task::done(Message::CloseModal)
                 }
             }
-            Message::RemoveTask(task_id) => {
-                iced::Task::perform(remove_task(self.db.clone(), task_id), |_| Message::NoOp).chain(
-                    iced::Task::perform(get_tasks(self.db.clone()), Message::TasksLoaded),
+            Message::ArchiveTask(task_id) => {
+                let Some(index) = self.tasks.iter().position(|task| task.id == task_id) else {
+                    return iced::Task::none();
+                };
+                let before = self.tasks.remove(index);
+                let archived_at = Utc::now();
+                let after = Task {
+                    archived_at: Some(archived_at),
+                    ..before.clone()
+                };
+                self.history.record(Change {
+                    before: vec![before],
+                    after: vec![after],
+                });
+                iced::Task::perform(
+                    self.repo.archive_task(task_id, Some(archived_at)),
+                    Message::Saved,
                 )
+                .chain(self.search())
+            }
+            Message::LoadArchive => {
+                self.purge_pending = None;
+                iced::Task::perform(
+                    self.repo.get_archived_tasks(self.board_id),
+                    Message::ArchiveLoaded,
+                )
+            }
+            Message::ArchiveLoaded(tasks) => {
+                if let Ok(tasks) = tasks {
+                    self.archived = tasks;
+                }
+                iced::Task::none()
+            }
+            Message::RestoreTask(task_id) => {
+                let Some(index) = self.archived.iter().position(|task| task.id == task_id) else {
+                    return iced::Task::none();
+                };
+                let before = self.archived[index].clone();
+                // Restored tasks go to the bottom of their lane
+                let after = Task {
+                    archived_at: None,
+                    position: next_position(&self.tasks, &before.lane),
+                    ..before.clone()
+                };
+                self.history.record(Change {
+                    before: vec![before.clone()],
+                    after: vec![after.clone()],
+                });
+                self.transition(vec![before], vec![after])
+            }
+            Message::ConfirmPurge(task_id) => {
+                self.purge_pending = Some(task_id);
+                iced::Task::none()
+            }
+            Message::CancelPurge => {
+                self.purge_pending = None;
+                iced::Task::none()
+            }
+            Message::PurgeTask(task_id) => {
+                self.purge_pending = None;
+                self.archived.retain(|task| task.id != task_id);
+                self.history.forget(task_id);
+                iced::Task::perform(self.repo.remove_task(task_id), Message::Saved)
             }
             Message::MoveToLane(new_lane, task_id) => {
-                let db = self.db.clone();
-                if let Some(task) = self.find_task_by_id_mut(task_id) {
-                    task.lane = new_lane;
-                    iced::Task::perform(edit_task(db, task.clone()), |_| Message::NoOp)
+                if self
+                    .find_task_by_id(task_id)
+                    .is_some_and(|task| task.lane != new_lane)
+                    && let Some(warning) = self.wip_warning(&new_lane)
+                {
+                    self.warning = Some(warning);
+                    return iced::Task::none();
+                }
+                self.warn_if_blocked(task_id, &new_lane);
+                let repo = self.repo.clone();
+                let position = next_position(&self.tasks, &new_lane);
+                match self.find_task_by_id_mut(task_id) {
+                    Some(task) if task.lane != new_lane => {
+                        let before = task.clone();
+                        task.lane = new_lane;
+                        task.position = position;
+                        task.updated_at = Utc::now();
+                        let task = task.clone();
+                        self.history.record(Change {
+                            before: vec![before],
+                            after: vec![task.clone()],
+                        });
+                        self.tasks.sort_by_key(|task| task.position);
+                        iced::Task::perform(repo.edit_task(task), Message::Saved)
+                    }
+                    _ => iced::Task::none(),
+                }
+            }
+            Message::MoveUp(task_id) | Message::MoveDown(task_id) => {
+                let Some(lane) = self.find_task_by_id(task_id).map(|t| t.lane.clone()) else {
+                    return iced::Task::none();
+                };
+                let ids = self.lane_task_ids(&lane);
+                let index = ids.iter().position(|id| *id == task_id).unwrap_or_default();
+                let index = if let Message::MoveUp(_) = msg {
+                    index.saturating_sub(1)
                 } else {
-                    iced::Task::none()
+                    index + 1
+                };
+                self.place_task(task_id, lane, index)
+            }
+            Message::Reorder(lane, index, task_id) => {
+                // Sorted lanes ignore manual order, so only moves into another lane count
+                if self.sort_by_priority
+                    && self
+                        .find_task_by_id(task_id)
+                        .is_some_and(|task| task.lane == lane)
+                {
+                    return iced::Task::none();
+                }
+                self.place_task(task_id, lane, index)
+            }
+            Message::DragStart(task_id) => {
+                // The cursor starts inside the card's lane, so no DragEnter will fire for it
+                self.dragging = Some(task_id);
+                self.drop_target = self
+                    .find_task_by_id(task_id)
+                    .map(|task| task.lane.clone())
+                    .filter(|lane| self.lanes.iter().any(|l| &l.name == lane));
+                iced::Task::none()
+            }
+            Message::DragEnter(lane) => {
+                if self.dragging.is_some() {
+                    self.drop_target = Some(lane);
+                }
+                iced::Task::none()
+            }
+            Message::DragLeave(lane) => {
+                if self.drop_target.as_ref() == Some(&lane) {
+                    self.drop_target = None;
+                }
+                iced::Task::none()
+            }
+            Message::CardReleased(task_id) => {
+                // Releasing a card over its own lane is a click, anything else is a drop
+                let task_lane = self.find_task_by_id(task_id).map(|t| t.lane.clone());
+                let is_click = self.dragging == Some(task_id)
+                    && (self.drop_target.is_none() || self.drop_target == task_lane);
+                if is_click {
+                    self.dragging = None;
+                    self.drop_target = None;
+                    iced::Task::done(Message::OpenModal(Modal::ViewTask(task_id)))
+                } else {
+                    self.drop_dragged_task(Some(task_id))
                 }
             }
+            Message::Drop => self.drop_dragged_task(None),
+            Message::DragEnded => {
+                self.dragging = None;
+                self.drop_target = None;
+                iced::Task::none()
+            }
             Message::OpenModal(modal) => {
-                if let Modal::EditTask(task_id) = modal {
-                    if let Some(task) = self.find_task_by_id(task_id) {
-                        let desc = task.description.clone();
-                        self.new_task_title = task.title.clone();
-                        if let Some(desc) = desc {
-                            self.new_task_description = text_editor::Content::with_text(&desc);
-                        }
+                if let Modal::EditTask(task_id) = modal
+                    && let Some(task) = self.find_task_by_id(task_id).cloned()
+                {
+                    self.new_task_title = task.title;
+                    self.new_task_priority = task.priority;
+                    self.new_task_labels = task.labels.iter().map(|label| label.id).collect();
+                    self.new_task_blocked_by = task.blocked_by.clone();
+                    self.new_task_due_date = task.due_date;
+                    self.due_date_month = task.due_date.unwrap_or(self.due_date_month);
+                    if let Some(desc) = task.description {
+                        self.new_task_description = text_editor::Content::with_text(&desc);
                     }
                 }
+                self.previewing = false;
+                self.markdown = match modal {
+                    Modal::ViewTask(task_id) => self
+                        .find_task_by_id(task_id)
+                        .and_then(|task| task.description.as_deref())
+                        .map(|desc| markdown::parse(desc).collect())
+                        .unwrap_or_default(),
+                    _ => vec![],
+                };
+                let load_events = match modal {
+                    Modal::ViewTask(task_id) => {
+                        iced::Task::perform(self.repo.get_task_events(task_id), move |events| {
+                            Message::EventsLoaded(task_id, events)
+                        })
+                    }
+                    _ => iced::Task::none(),
+                };
+                self.events.clear();
                 self.modal = Some(modal);
-                iced::Task::none()
+                load_events
             }
             Message::CloseModal => {
                 self.hide_dialog();
@@ -162,115 +718,480 @@ impl ViewController {
                 self.new_task_description.perform(action);
                 iced::Task::none()
             }
-            Message::NoOp => iced::Task::none(),
+            Message::DescriptionPreviewToggled(previewing) => {
+                self.previewing = previewing;
+                self.markdown = if previewing {
+                    markdown::parse(&self.new_task_description.text()).collect()
+                } else {
+                    vec![]
+                };
+                iced::Task::none()
+            }
+            Message::LinkClicked(url) => {
+                let opened = open::that_detached(url.as_str()).map_err(|err| Error::Io {
+                    action: "open the link",
+                    message: format!("{url}: {err}"),
+                });
+                iced::Task::done(Message::LinkOpened(opened))
+            }
+            Message::TaskDueDatePicked(due_date) => {
+                self.new_task_due_date = due_date;
+                if let Some(due_date) = due_date {
+                    self.due_date_month = due_date;
+                }
+                iced::Task::none()
+            }
+            Message::TaskPriorityPicked(priority) => {
+                self.new_task_priority = priority;
+                iced::Task::none()
+            }
+            Message::TaskLabelToggled(label_id, selected) => {
+                self.new_task_labels.retain(|id| *id != label_id);
+                if selected {
+                    self.new_task_labels.push(label_id);
+                }
+                iced::Task::none()
+            }
+            Message::SubtaskToggled(task_id, subtask_id, done) => {
+                let subtask = self
+                    .find_task_by_id_mut(task_id)
+                    .and_then(|task| task.subtasks.iter_mut().find(|s| s.id == subtask_id));
+                if let Some(subtask) = subtask {
+                    subtask.done = done;
+                }
+                iced::Task::perform(self.repo.set_subtask_done(subtask_id, done), Message::Saved)
+            }
+            Message::NewSubtaskTitleUpdated(title) => {
+                self.new_subtask_title = title;
+                iced::Task::none()
+            }
+            Message::AddSubtask(task_id) => {
+                let title = self.new_subtask_title.trim().to_string();
+                if title.is_empty() {
+                    return iced::Task::none();
+                }
+                self.new_subtask_title.clear();
+                iced::Task::perform(
+                    self.repo.insert_subtask(task_id, title),
+                    Message::SubtaskAdded,
+                )
+            }
+            Message::SubtaskAdded(subtask) => {
+                if let Ok(subtask) = subtask
+                    && let Some(task) = self.find_task_by_id_mut(subtask.task_id)
+                {
+                    task.subtasks.push(subtask);
+                }
+                iced::Task::none()
+            }
+            Message::RemoveSubtask(task_id, subtask_id) => {
+                if let Some(task) = self.find_task_by_id_mut(task_id) {
+                    task.subtasks.retain(|s| s.id != subtask_id);
+                }
+                iced::Task::perform(self.repo.remove_subtask(subtask_id), Message::Saved)
+            }
+            Message::BlockerPicked(blocker) => {
+                if !self.new_task_blocked_by.contains(&blocker.id) {
+                    self.new_task_blocked_by.push(blocker.id);
+                }
+                iced::Task::none()
+            }
+            Message::BlockerRemoved(blocker_id) => {
+                self.new_task_blocked_by.retain(|id| *id != blocker_id);
+                iced::Task::none()
+            }
+            Message::NewLabelNameUpdated(name) => {
+                self.new_label_name = name;
+                iced::Task::none()
+            }
+            Message::CreateLabel => {
+                let name = self.new_label_name.trim().to_string();
+                if name.is_empty() {
+                    return iced::Task::none();
+                }
+                // Reuse an existing label rather than tripping the unique constraint
+                if let Some(label) = self.labels.iter().find(|label| label.name == name) {
+                    let label_id = label.id;
+                    self.new_label_name.clear();
+                    return iced::Task::done(Message::TaskLabelToggled(label_id, true));
+                }
+                iced::Task::perform(self.repo.insert_label(name), Message::LabelCreated)
+            }
+            Message::LabelCreated(label) => {
+                if let Ok(label) = label {
+                    self.new_label_name.clear();
+                    self.new_task_labels.push(label.id);
+                    self.labels.push(label);
+                    self.labels.sort_by(|a, b| a.name.cmp(&b.name));
+                }
+                iced::Task::none()
+            }
+            Message::SearchTextUpdated(text) => {
+                self.filter.text = text;
+                self.filter.matching_ids = None;
+                self.search_results.clear();
+                self.search()
+            }
+            Message::SearchResults(query, results) => {
+                // Results for a query the user has since changed are stale
+                if query == self.filter.text
+                    && let Ok(results) = results
+                {
+                    self.filter.matching_ids = Some(results.iter().map(|m| m.task_id).collect());
+                    self.search_results = results;
+                }
+                iced::Task::none()
+            }
+            Message::EventsLoaded(task_id, events) => {
+                // Ignore events for a task whose dialog has since been closed
+                if let Some(Modal::ViewTask(open_id)) = self.modal
+                    && open_id == task_id
+                    && let Ok(events) = events
+                {
+                    self.events = events;
+                }
+                iced::Task::none()
+            }
+            Message::LabelFilterPicked(label) => {
+                self.filter.label = Some(label.id);
+                iced::Task::none()
+            }
+            Message::PriorityFilterPicked(priority) => {
+                self.filter.priority = Some(priority);
+                iced::Task::none()
+            }
+            Message::DueFilterPicked(due) => {
+                self.filter.due = Some(due);
+                iced::Task::none()
+            }
+            Message::ClearFilters => {
+                self.filter = Filter::default();
+                self.search_results.clear();
+                iced::Task::none()
+            }
+            Message::DueDateMonthChanged(month) => {
+                self.due_date_month = month;
+                iced::Task::none()
+            }
+            Message::DismissWarning => {
+                self.warning = None;
+                iced::Task::none()
+            }
+            Message::Undo => match self.history.undo() {
+                Some(change) => self.transition(change.after, change.before),
+                None => iced::Task::none(),
+            },
+            Message::Redo => match self.history.redo() {
+                Some(change) => self.transition(change.before, change.after),
+                None => iced::Task::none(),
+            },
+            // A failed write leaves the board out of step with the database
+            Message::Saved(Err(_)) => self.load(),
+            Message::Saved(Ok(())) | Message::LinkOpened(_) | Message::NoOp => iced::Task::none(),
         }
     }
 
-    pub fn view(&self) -> Element<Message> {
+    pub fn view(&self) -> Element<'_, Message> {
         let mut grouped_by_lane: HashMap<&str, Vec<&Task>> = HashMap::new();
 
-        for task in &self.tasks {
+        for task in self.tasks.iter().filter(|t| self.filter.matches(t)) {
             grouped_by_lane.entry(&task.lane).or_default().push(task);
         }
 
-        let lanes = self.lanes.iter().enumerate().map(|(idx, lane)| {
-            let tasks = grouped_by_lane.remove(lane.as_str()).unwrap_or_default();
-            let elems = tasks
+        // Manual ordering is hidden while lanes are sorted by priority or filtered
+        let manual_order = !self.sort_by_priority && !self.filter.is_active();
+        let mut lanes: Vec<Element<Message>> = self
+            .lanes
+            .iter()
+            .enumerate()
+            .map(|(idx, lane)| {
+                let mut tasks = grouped_by_lane
+                    .remove(lane.name.as_str())
+                    .unwrap_or_default();
+                if self.sort_by_priority {
+                    tasks.sort_by_key(|t| Reverse(t.priority));
+                }
+                let elems = tasks
+                    .iter()
+                    .enumerate()
+                    .map(|(pos, t)| {
+                        let next_lane = self
+                            .lanes
+                            .get(idx + 1)
+                            .map(|lane| Message::MoveToLane(lane.name.clone(), t.id));
+                        let move_up = (manual_order && pos > 0).then_some(Message::MoveUp(t.id));
+                        let move_down = (manual_order && pos + 1 < tasks.len())
+                            .then_some(Message::MoveDown(t.id));
+                        task_card(
+                            t,
+                            self.dragging == Some(t.id),
+                            !t.open_blockers(&self.tasks, &self.lanes).is_empty(),
+                            CardActions {
+                                archive: Message::ArchiveTask(t.id),
+                                grab: Message::DragStart(t.id),
+                                release: Message::CardReleased(t.id),
+                                move_up,
+                                move_down,
+                                next_lane,
+                            },
+                        )
+                    })
+                    .collect();
+
+                // WIP counts every task in the lane, not just those matching the filter
+                let (title, over_limit) = match lane.wip_limit {
+                    Some(limit) => {
+                        let count = self.lane_task_ids(&lane.name).len();
+                        (
+                            format!("{} ({}/{})", lane.name, count, limit),
+                            count > limit as usize,
+                        )
+                    }
+                    None => (format!("{} ({})", lane.name, tasks.len()), false),
+                };
+                let is_drop_target =
+                    self.dragging.is_some() && self.drop_target.as_ref() == Some(&lane.name);
+                swim_lane(
+                    title,
+                    over_limit,
+                    elems,
+                    is_drop_target,
+                    Message::DragEnter(lane.name.clone()),
+                    Message::DragLeave(lane.name.clone()),
+                    Message::Drop,
+                )
+            })
+            .collect();
+
+        // Tasks whose lane is no longer configured are surfaced in their own lane
+        // so they can be moved back onto the board.
+        let mut orphaned: Vec<&Task> = grouped_by_lane.into_values().flatten().collect();
+        if !orphaned.is_empty() {
+            orphaned.sort_by(|a, b| a.lane.cmp(&b.lane).then(a.position.cmp(&b.position)));
+            let elems = orphaned
                 .iter()
                 .map(|t| {
-                    let next_lane = self
+                    let first_lane = self
                         .lanes
-                        .get(idx + 1)
-                        .map(|lane| Message::MoveToLane(lane.clone(), t.id));
+                        .first()
+                        .map(|lane| Message::MoveToLane(lane.name.clone(), t.id));
                     task_card(
                         t,
-                        Message::RemoveTask(t.id),
-                        Message::OpenModal(Modal::ViewTask(t.id)),
-                        next_lane,
+                        self.dragging == Some(t.id),
+                        !t.open_blockers(&self.tasks, &self.lanes).is_empty(),
+                        CardActions {
+                            archive: Message::ArchiveTask(t.id),
+                            grab: Message::DragStart(t.id),
+                            release: Message::CardReleased(t.id),
+                            move_up: None,
+                            move_down: None,
+                            next_lane: first_lane,
+                        },
                     )
                 })
                 .collect();
 
-            let title = format!("{} ({})", lane, tasks.len());
-            swim_lane(title, elems)
+            let title = format!("{} ({})", UNASSIGNED, orphaned.len());
+            lanes.push(swim_lane(
+                title,
+                false,
+                elems,
+                false,
+                Message::NoOp,
+                Message::NoOp,
+                Message::DragEnded,
+            ));
+        }
+
+        let board = row(lanes).spacing(24);
+        match &self.warning {
+            Some(warning) => column![warning_banner(warning, Message::DismissWarning), board]
+                .spacing(8)
+                .into(),
+            None => board.into(),
+        }
+    }
+
+    pub fn filter_view(&self) -> Element<'_, Message> {
+        let selected_label = self
+            .filter
+            .label
+            .and_then(|id| self.labels.iter().find(|label| label.id == id))
+            .cloned();
+        let filters = row![
+            text_input("Search tasks", &self.filter.text)
+                .on_input(Message::SearchTextUpdated)
+                .width(Length::Fill),
+            pick_list(
+                self.labels.as_slice(),
+                selected_label,
+                Message::LabelFilterPicked
+            )
+            .placeholder("Any label"),
+            pick_list(
+                Priority::ALL,
+                self.filter.priority,
+                Message::PriorityFilterPicked
+            )
+            .placeholder("Any priority"),
+            pick_list(DueFilter::ALL, self.filter.due, Message::DueFilterPicked)
+                .placeholder("Any due date"),
+            button("Clear")
+                .on_press_maybe(self.filter.is_active().then_some(Message::ClearFilters)),
+        ]
+        .spacing(8);
+
+        if self.filter.text.trim().is_empty() || self.search_results.is_empty() {
+            return filters.into();
+        }
+
+        let results = self
+            .search_results
+            .iter()
+            .take(SEARCH_RESULTS_SHOWN)
+            .map(|m| {
+                search_result(
+                    &m.title,
+                    &m.snippet,
+                    Message::OpenModal(Modal::ViewTask(m.task_id)),
+                )
+            });
+        column![filters, Column::with_children(results).spacing(4)]
+            .spacing(8)
+            .into()
+    }
+
+    pub fn archive_view(&self) -> Element<'_, Message> {
+        if self.archived.is_empty() {
+            return text("No archived tasks").into();
+        }
+        let rows = self.archived.iter().map(|task| {
+            let pending = self.purge_pending == Some(task.id);
+            archived_task_row(
+                task,
+                Message::RestoreTask(task.id),
+                if pending {
+                    Message::PurgeTask(task.id)
+                } else {
+                    Message::ConfirmPurge(task.id)
+                },
+                pending.then_some(Message::CancelPurge),
+            )
         });
+        scrollable(Column::with_children(rows).spacing(4)).into()
+    }
 
-        row(lanes).spacing(24).into()
+    fn task_form(&self, modal_title: String, submit: Message) -> Element<'_, Message> {
+        let priority = pick_list(
+            Priority::ALL,
+            Some(self.new_task_priority),
+            Message::TaskPriorityPicked,
+        )
+        .into();
+        let labels = label_picker(
+            &self.labels,
+            &self.new_task_labels,
+            Message::TaskLabelToggled,
+            &self.new_label_name,
+            Message::NewLabelNameUpdated,
+            Message::CreateLabel,
+        );
+        let due_date = date_picker(
+            self.new_task_due_date,
+            self.due_date_month,
+            Message::TaskDueDatePicked,
+            Message::DueDateMonthChanged,
+        );
+        let blockers = self
+            .new_task_blocked_by
+            .iter()
+            .map(|id| match self.find_task_by_id(*id) {
+                Some(task) => TaskRef::from(task),
+                None => TaskRef {
+                    id: *id,
+                    title: String::new(),
+                },
+            })
+            .collect();
+        let blocked_by = dependency_picker(
+            blockers,
+            self.blocker_options(),
+            Message::BlockerPicked,
+            Message::BlockerRemoved,
+        );
+        let mut fields = vec![
+            ("Priority", priority),
+            ("Due date", due_date),
+            ("Labels", labels),
+            ("Blocked by", blocked_by),
+        ];
+        // Checklist items belong to a saved task, so they can only be added when editing
+        if let Some(Modal::EditTask(task_id)) = self.modal
+            && let Some(task) = self.find_task_by_id(task_id)
+        {
+            fields.push((
+                "Checklist",
+                checklist_editor(
+                    &task.subtasks,
+                    move |subtask_id, done| Message::SubtaskToggled(task_id, subtask_id, done),
+                    move |subtask_id| Message::RemoveSubtask(task_id, subtask_id),
+                    &self.new_subtask_title,
+                    Message::NewSubtaskTitleUpdated,
+                    Message::AddSubtask(task_id),
+                ),
+            ));
+        }
+        task_dialog_mut(TaskForm {
+            modal_title,
+            title: &self.new_task_title,
+            description: &self.new_task_description,
+            title_update: &Message::TaskTitleUpdated,
+            description_update: &Message::TaskDescUpdated,
+            preview: self
+                .previewing
+                .then(|| markdown_text(&self.markdown, Message::LinkClicked)),
+            toggle_preview: &Message::DescriptionPreviewToggled,
+            fields,
+            warning: self.warning.as_deref(),
+            submit,
+            cancel: Message::CloseModal,
+        })
     }
 
-    pub fn modal_view(&self) -> Option<Element<Message>> {
+    pub fn modal_view(&self) -> Option<Element<'_, Message>> {
         match self.modal {
             Some(Modal::ViewTask(task_id)) => {
                 let maybe_task = self.find_task_by_id(task_id);
                 maybe_task.map(|t| {
+                    let link = |task: &Task| {
+                        (
+                            TaskRef::from(task).to_string(),
+                            Message::OpenModal(Modal::ViewTask(task.id)),
+                        )
+                    };
+                    let blocked_by = t
+                        .blocked_by
+                        .iter()
+                        .filter_map(|id| self.find_task_by_id(*id))
+                        .map(link)
+                        .collect();
+                    let blocking = self.dependents(t.id).into_iter().map(link).collect();
                     task_dialog(
                         t,
+                        &self.events,
+                        markdown_text(&self.markdown, Message::LinkClicked),
+                        move |subtask_id, done| Message::SubtaskToggled(task_id, subtask_id, done),
+                        vec![("Blocked by", blocked_by), ("Blocking", blocking)],
                         Message::OpenModal(Modal::EditTask(t.id)),
                         Message::CloseModal,
                     )
                 })
             }
-            Some(Modal::NewTask) => Some(task_dialog_mut(
-                "New Task".into(),
-                &self.new_task_title,
-                &self.new_task_description,
-                &Message::TaskTitleUpdated,
-                &Message::TaskDescUpdated,
-                Message::CreateTask,
-                Message::CloseModal,
-            )),
-            Some(Modal::EditTask(task_id)) => Some(task_dialog_mut(
-                "Edit Task".into(),
-                &self.new_task_title,
-                &self.new_task_description,
-                &Message::TaskTitleUpdated,
-                &Message::TaskDescUpdated,
-                Message::EditTask(task_id),
-                Message::CloseModal,
-            )),
+            Some(Modal::NewTask) => Some(self.task_form("New Task".into(), Message::CreateTask)),
+            Some(Modal::EditTask(task_id)) => {
+                Some(self.task_form("Edit Task".into(), Message::EditTask(task_id)))
+            }
             None => None,
         }
     }
 }
-
-pub async fn get_tasks(pool: Pool<Sqlite>) -> Result<Vec<Task>, String> {
-    sqlx::query_as!(Task, "SELECT id, title, description, lane FROM tasks")
-        .fetch_all(&pool)
-        .map_err(|err| format!("got db err: {err}"))
-        .await
-}
-
-async fn insert_task(pool: Pool<Sqlite>, t: NewTask) -> Result<(), String> {
-    sqlx::query!(
-        "INSERT INTO tasks (title, description, lane) VALUES (?, ?, ?)",
-        t.title,
-        t.description,
-        t.lane
-    )
-    .execute(&pool)
-    .map_err(|_| "Error inserting task into db".into())
-    .map_ok(|_| ())
-    .await
-}
-
-async fn remove_task(pool: Pool<Sqlite>, task_id: i64) -> Result<(), String> {
-    sqlx::query!("DELETE FROM tasks WHERE id = ?", task_id)
-        .execute(&pool)
-        .map_err(|_| "Error deleting task from db".into())
-        .map_ok(|_| ())
-        .await
-}
-
-async fn edit_task(pool: Pool<Sqlite>, task: Task) -> Result<(), String> {
-    sqlx::query!(
-        "UPDATE tasks SET title = ?, description = ?, lane = ? WHERE id = ?",
-        task.title,
-        task.description,
-        task.lane,
-        task.id
-    )
-    .execute(&pool)
-    .map_err(|_| "Error deleting task from db".into())
-    .map_ok(|_| ())
-    .await
-}
@@ -0,0 +1,486 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Flex, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{Block, Clear, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::error::Error;
+use crate::lane::Lane;
+use crate::model::{NewTask, Priority, Task, next_position};
+use crate::repository::TaskRepository;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+const BOARD_HELP: &str = "←→ lane  ↑↓ task  H/L move  Enter open  a add  e title  d description  x archive  r reload  q quit";
+const DETAIL_HELP: &str = "↑↓ item  Space check  e title  d description  p priority  Esc back";
+
+enum Mode {
+    Board,
+    Detail(usize),
+    Input(Input),
+}
+
+enum InputKind {
+    NewTask,
+    Title(i64),
+    Description(i64),
+}
+
+struct Input {
+    kind: InputKind,
+    buffer: String,
+}
+
+impl Input {
+    fn title(&self) -> &'static str {
+        match self.kind {
+            InputKind::NewTask => " New task ",
+            InputKind::Title(_) => " Title ",
+            InputKind::Description(_) => " Description (Ctrl+S to save) ",
+        }
+    }
+
+    // Descriptions span several lines, so Enter only submits single-line input
+    fn is_multiline(&self) -> bool {
+        matches!(self.kind, InputKind::Description(_))
+    }
+}
+
+struct Tui<R> {
+    repo: R,
+    board_id: i64,
+    lanes: Vec<Lane>,
+    tasks: Vec<Task>,
+    lane: usize,
+    selected: usize,
+    mode: Mode,
+    status: Option<String>,
+    quit: bool,
+}
+
+pub async fn run(repo: impl TaskRepository, board_id: i64, lanes: Vec<Lane>) -> Result<(), Error> {
+    let mut tui = Tui {
+        repo,
+        board_id,
+        lanes,
+        tasks: vec![],
+        lane: 0,
+        selected: 0,
+        mode: Mode::Board,
+        status: None,
+        quit: false,
+    };
+    tui.reload().await?;
+
+    let mut terminal = ratatui::init();
+    let result = tui.run(&mut terminal).await;
+    ratatui::restore();
+    result
+}
+
+impl<R: TaskRepository> Tui<R> {
+    async fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<(), Error> {
+        while !self.quit {
+            terminal
+                .draw(|frame| self.draw(frame))
+                .map_err(Error::io("draw the terminal"))?;
+            let has_event =
+                event::poll(POLL_INTERVAL).map_err(Error::io("read terminal events"))?;
+            if !has_event {
+                continue;
+            }
+            if let Event::Key(key) = event::read().map_err(Error::io("read terminal events"))?
+                && key.kind == KeyEventKind::Press
+            {
+                self.status = None;
+                if let Err(err) = self.handle_key(key).await {
+                    self.status = Some(err.to_string());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn reload(&mut self) -> Result<(), Error> {
+        self.tasks = self.repo.get_tasks(self.board_id).await?;
+        self.tasks.sort_by_key(|task| task.position);
+        self.lane = self.lane.min(self.lanes.len().saturating_sub(1));
+        self.selected = self
+            .selected
+            .min(self.lane_tasks(self.lane).len().saturating_sub(1));
+        Ok(())
+    }
+
+    fn lane_tasks(&self, lane: usize) -> Vec<&Task> {
+        match self.lanes.get(lane) {
+            Some(lane) => self.tasks.iter().filter(|t| t.lane == lane.name).collect(),
+            None => vec![],
+        }
+    }
+
+    fn selected_task(&self) -> Option<&Task> {
+        self.lane_tasks(self.lane).get(self.selected).copied()
+    }
+
+    fn find_task(&self, task_id: i64) -> Option<&Task> {
+        self.tasks.iter().find(|task| task.id == task_id)
+    }
+
+    fn wip_warning(&self, lane: usize) -> Option<String> {
+        self.lanes
+            .get(lane)?
+            .wip_warning(self.lane_tasks(lane).len())
+    }
+
+    async fn handle_key(&mut self, key: KeyEvent) -> Result<(), Error> {
+        match &mut self.mode {
+            Mode::Board => self.handle_board_key(key).await,
+            Mode::Detail(_) => self.handle_detail_key(key).await,
+            Mode::Input(input) => match key.code {
+                KeyCode::Esc => {
+                    self.mode = Mode::Board;
+                    Ok(())
+                }
+                KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    self.submit_input().await
+                }
+                KeyCode::Enter if !input.is_multiline() => self.submit_input().await,
+                KeyCode::Enter => {
+                    input.buffer.push('\n');
+                    Ok(())
+                }
+                KeyCode::Backspace => {
+                    input.buffer.pop();
+                    Ok(())
+                }
+                KeyCode::Char(c) => {
+                    input.buffer.push(c);
+                    Ok(())
+                }
+                _ => Ok(()),
+            },
+        }
+    }
+
+    async fn handle_board_key(&mut self, key: KeyEvent) -> Result<(), Error> {
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Char('H') => self.move_selected(-1).await?,
+            KeyCode::Char('L') => self.move_selected(1).await?,
+            KeyCode::Left if key.modifiers.contains(KeyModifiers::SHIFT) => {
+                self.move_selected(-1).await?
+            }
+            KeyCode::Right if key.modifiers.contains(KeyModifiers::SHIFT) => {
+                self.move_selected(1).await?
+            }
+            KeyCode::Left | KeyCode::Char('h') => {
+                self.lane = self.lane.saturating_sub(1);
+                self.selected = 0;
+            }
+            KeyCode::Right | KeyCode::Char('l') => {
+                self.lane = (self.lane + 1).min(self.lanes.len().saturating_sub(1));
+                self.selected = 0;
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => {
+                let count = self.lane_tasks(self.lane).len();
+                self.selected = (self.selected + 1).min(count.saturating_sub(1));
+            }
+            KeyCode::Enter if self.selected_task().is_some() => self.mode = Mode::Detail(0),
+            KeyCode::Char('a') => {
+                self.mode = Mode::Input(Input {
+                    kind: InputKind::NewTask,
+                    buffer: String::new(),
+                })
+            }
+            KeyCode::Char('e') | KeyCode::Char('d') => self.start_edit(key.code),
+            KeyCode::Char('x') => {
+                if let Some(task) = self.selected_task() {
+                    self.repo.archive_task(task.id, Some(Utc::now())).await?;
+                    self.reload().await?;
+                }
+            }
+            KeyCode::Char('r') => self.reload().await?,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    async fn handle_detail_key(&mut self, key: KeyEvent) -> Result<(), Error> {
+        let Mode::Detail(item) = self.mode else {
+            return Ok(());
+        };
+        let Some(task) = self.selected_task().cloned() else {
+            self.mode = Mode::Board;
+            return Ok(());
+        };
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.mode = Mode::Board,
+            KeyCode::Up | KeyCode::Char('k') => self.mode = Mode::Detail(item.saturating_sub(1)),
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.mode = Mode::Detail((item + 1).min(task.subtasks.len().saturating_sub(1)))
+            }
+            KeyCode::Char(' ') => {
+                if let Some(subtask) = task.subtasks.get(item) {
+                    self.repo
+                        .set_subtask_done(subtask.id, !subtask.done)
+                        .await?;
+                    self.reload().await?;
+                }
+            }
+            KeyCode::Char('p') => {
+                let next = Priority::ALL
+                    .iter()
+                    .position(|p| *p == task.priority)
+                    .map(|idx| Priority::ALL[(idx + 1) % Priority::ALL.len()])
+                    .unwrap_or_default();
+                self.repo
+                    .edit_task(Task {
+                        priority: next,
+                        ..task
+                    })
+                    .await?;
+                self.reload().await?;
+            }
+            KeyCode::Char('e') | KeyCode::Char('d') => self.start_edit(key.code),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn start_edit(&mut self, key: KeyCode) {
+        let Some(task) = self.selected_task() else {
+            return;
+        };
+        let input = if key == KeyCode::Char('e') {
+            Input {
+                kind: InputKind::Title(task.id),
+                buffer: task.title.clone(),
+            }
+        } else {
+            Input {
+                kind: InputKind::Description(task.id),
+                buffer: task.description.clone().unwrap_or_default(),
+            }
+        };
+        self.mode = Mode::Input(input);
+    }
+
+    async fn submit_input(&mut self) -> Result<(), Error> {
+        let Mode::Input(input) = std::mem::replace(&mut self.mode, Mode::Board) else {
+            return Ok(());
+        };
+        match input.kind {
+            InputKind::NewTask => {
+                let title = input.buffer.trim().to_string();
+                if title.is_empty() {
+                    return Ok(());
+                }
+                if let Some(warning) = self.wip_warning(0) {
+                    return Err(Error::Invalid(warning));
+                }
+                let lane = self
+                    .lanes
+                    .first()
+                    .ok_or_else(|| Error::Invalid("The board has no lanes".into()))?;
+                let task = NewTask::new(
+                    self.board_id,
+                    title,
+                    None,
+                    lane.name.clone(),
+                    None,
+                    Priority::default(),
+                    vec![],
+                );
+                self.repo.insert_task(task).await?;
+                self.lane = 0;
+                self.reload().await?;
+                self.selected = self.lane_tasks(0).len().saturating_sub(1);
+            }
+            InputKind::Title(task_id) | InputKind::Description(task_id) => {
+                let Some(mut task) = self.find_task(task_id).cloned() else {
+                    return Ok(());
+                };
+                if let InputKind::Title(_) = input.kind {
+                    if input.buffer.trim().is_empty() {
+                        return Err(Error::Invalid("Titles cannot be empty".into()));
+                    }
+                    task.title = input.buffer.trim().to_string();
+                } else {
+                    task.description = Some(input.buffer);
+                }
+                self.repo.edit_task(task).await?;
+                self.reload().await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn move_selected(&mut self, offset: isize) -> Result<(), Error> {
+        let Some(mut task) = self.selected_task().cloned() else {
+            return Ok(());
+        };
+        let Some(target) = self
+            .lane
+            .checked_add_signed(offset)
+            .filter(|lane| *lane < self.lanes.len())
+        else {
+            return Ok(());
+        };
+        if let Some(warning) = self.wip_warning(target) {
+            return Err(Error::Invalid(warning));
+        }
+        let lane = self.lanes[target].name.clone();
+        if let Some(warning) = task.blocked_warning(&lane, &self.tasks, &self.lanes) {
+            self.status = Some(warning);
+        }
+
+        task.position = next_position(&self.tasks, &lane);
+        task.lane = lane;
+        self.repo.edit_task(task).await?;
+        self.lane = target;
+        self.reload().await?;
+        self.selected = self.lane_tasks(target).len().saturating_sub(1);
+        Ok(())
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [board_area, status_area] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+        let columns = Layout::horizontal(vec![
+            Constraint::Ratio(1, self.lanes.len().max(1) as u32);
+            self.lanes.len()
+        ])
+        .split(board_area);
+
+        for (idx, lane) in self.lanes.iter().enumerate() {
+            let tasks = self.lane_tasks(idx);
+            let (title, over_limit) = match lane.wip_limit {
+                Some(limit) => (
+                    format!(" {} ({}/{}) ", lane.name, tasks.len(), limit),
+                    tasks.len() > limit as usize,
+                ),
+                None => (format!(" {} ({}) ", lane.name, tasks.len()), false),
+            };
+            let mut block = Block::bordered().title(title);
+            if over_limit {
+                block = block.title_style(Style::new().fg(Color::Red));
+            }
+            if idx == self.lane {
+                block = block.border_style(Style::new().fg(Color::Cyan));
+            }
+
+            let items: Vec<ListItem> = tasks.iter().map(|task| self.card(task)).collect();
+            let mut state = ListState::default();
+            if idx == self.lane && !items.is_empty() {
+                state.select(Some(self.selected));
+            }
+            let list = List::new(items)
+                .block(block)
+                .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+            frame.render_stateful_widget(list, columns[idx], &mut state);
+        }
+
+        let status = match (&self.status, &self.mode) {
+            (Some(status), _) => Line::from(status.as_str()).red(),
+            (None, Mode::Detail(_)) => Line::from(DETAIL_HELP).dim(),
+            (None, _) => Line::from(BOARD_HELP).dim(),
+        };
+        frame.render_widget(status, status_area);
+
+        match &self.mode {
+            Mode::Board => {}
+            Mode::Detail(item) => {
+                if let Some(task) = self.selected_task() {
+                    self.draw_detail(frame, task, *item);
+                }
+            }
+            Mode::Input(input) => {
+                let area = popup_area(frame.area(), 60, if input.is_multiline() { 50 } else { 20 });
+                frame.render_widget(Clear, area);
+                frame.render_widget(
+                    Paragraph::new(format!("{}_", input.buffer))
+                        .wrap(Wrap { trim: false })
+                        .block(Block::bordered().title(input.title())),
+                    area,
+                );
+            }
+        }
+    }
+
+    fn card<'a>(&self, task: &'a Task) -> ListItem<'a> {
+        let mut meta = vec![Span::from(format!("#{} {}", task.id, task.priority)).dim()];
+        if let Some(due_date) = task.due_date {
+            meta.push(Span::from(format!("  due {}", due_date.format("%Y-%m-%d"))));
+        }
+        if !task.subtasks.is_empty() {
+            let done = task.subtasks.iter().filter(|s| s.done).count();
+            meta.push(Span::from(format!("  {}/{}", done, task.subtasks.len())));
+        }
+        if !task.open_blockers(&self.tasks, &self.lanes).is_empty() {
+            meta.push(Span::from("  blocked").red());
+        }
+        ListItem::new(vec![
+            Line::from(task.title.as_str()).bold(),
+            Line::from(meta),
+        ])
+    }
+
+    fn draw_detail(&self, frame: &mut Frame, task: &Task, item: usize) {
+        let mut lines = vec![
+            Line::from(format!("#{} {}", task.id, task.title)).bold(),
+            Line::from(format!("Lane: {}   Priority: {}", task.lane, task.priority)),
+        ];
+        if let Some(due_date) = task.due_date {
+            lines.push(Line::from(format!("Due: {}", due_date.format("%Y-%m-%d"))));
+        }
+        if !task.labels.is_empty() {
+            let labels: Vec<&str> = task.labels.iter().map(|l| l.name.as_str()).collect();
+            lines.push(Line::from(format!("Labels: {}", labels.join(", "))));
+        }
+        let blockers: Vec<String> = task
+            .blocked_by
+            .iter()
+            .filter_map(|id| self.find_task(*id))
+            .map(|blocker| format!("#{} {}", blocker.id, blocker.title))
+            .collect();
+        if !blockers.is_empty() {
+            lines.push(Line::from(format!("Blocked by: {}", blockers.join(", "))));
+        }
+        lines.push(Line::default());
+        if let Some(desc) = &task.description {
+            lines.extend(Text::from(desc.as_str()).lines);
+            lines.push(Line::default());
+        }
+        for (idx, subtask) in task.subtasks.iter().enumerate() {
+            let line = Line::from(format!(
+                "[{}] {}",
+                if subtask.done { "x" } else { " " },
+                subtask.title
+            ));
+            lines.push(if idx == item { line.reversed() } else { line });
+        }
+
+        let area = popup_area(frame.area(), 70, 70);
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(lines)
+                .wrap(Wrap { trim: false })
+                .block(Block::bordered().title(" Task ")),
+            area,
+        );
+    }
+}
+
+fn popup_area(area: Rect, percent_x: u16, percent_y: u16) -> Rect {
+    let [area] = Layout::vertical([Constraint::Percentage(percent_y)])
+        .flex(Flex::Center)
+        .areas(area);
+    let [area] = Layout::horizontal([Constraint::Percentage(percent_x)])
+        .flex(Flex::Center)
+        .areas(area);
+    area
+}